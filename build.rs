@@ -0,0 +1,17 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Exposes the sled version as SLED_VERSION so the Sled engine can report the
+// library it was built against. Cargo.toml pins sled to one exact version,
+// which is the only version that can be linked, so that's what is read here.
+fn main() {
+    let manifest = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("Cargo.toml");
+    println!("cargo:rerun-if-changed={}", manifest.display());
+    let contents = fs::read_to_string(&manifest).unwrap();
+    let version = contents.lines()
+        .find_map(|line| line.strip_prefix("sled = \"="))
+        .and_then(|rest| rest.strip_suffix('"'))
+        .unwrap_or_else(|| panic!("{} must pin sled to an exact version, e.g. sled = \"=0.34.7\"", manifest.display()));
+    println!("cargo:rustc-env=SLED_VERSION={}", version);
+}
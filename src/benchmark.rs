@@ -1,74 +1,757 @@
-use std::path::Path;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::fs;
+use std::io::Write;
+use std::borrow::Cow;
+use std::hash::{Hash, Hasher};
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
 use hdrhistogram::Histogram;
+use sled::Transactional;
+use hdrhistogram::serialization::{Deserializer, Serializer, V2DeflateSerializer};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use crate::corpus::Corpus;
+use crate::json::{self, Json};
+use crate::trace::{TraceReader, TraceRecord, TraceWriter};
+
+pub type KvPairs = Vec<(Vec<u8>, Vec<u8>)>;
+
+#[derive(Debug, Clone)]
+pub enum Op {
+    Put { key: Vec<u8>, value: Vec<u8> },
+    Delete { key: Vec<u8> },
+}
 
 pub trait StorageEngine: Send + Sync {
     fn put(&self, key: &[u8], value: &[u8]) -> Result<(), Box<dyn std::error::Error>>;
     fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>>;
-    fn range_scan(&self, start: &[u8], limit: usize) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Box<dyn std::error::Error>>;
+    // Looks up several keys at once; the default issues one get per key
+    fn multi_get(&self, keys: &[Vec<u8>]) -> Result<Vec<Option<Vec<u8>>>, Box<dyn std::error::Error>> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
+    fn delete(&self, key: &[u8]) -> Result<(), Box<dyn std::error::Error>>;
+    fn range_scan(&self, start: &[u8], limit: usize) -> Result<KvPairs, Box<dyn std::error::Error>>;
+    fn prefix_scan(&self, prefix: &[u8]) -> Result<KvPairs, Box<dyn std::error::Error>>;
+    // Writes pairs through the engine's bulk loading path; the default puts them one by one
+    fn bulk_load(&self, pairs: KvPairs) -> Result<(), Box<dyn std::error::Error>> {
+        for (key, value) in pairs {
+            self.put(&key, &value)?;
+        }
+        Ok(())
+    }
+    // The order range scans return keys in
+    fn compare_keys(&self, a: &[u8], b: &[u8]) -> std::cmp::Ordering {
+        a.cmp(b)
+    }
+    // Iterates every key once, returning the number of keys and key + value
+    // bytes seen. The default collects everything through range_scan.
+    fn full_scan(&self) -> Result<(u64, u64), Box<dyn std::error::Error>> {
+        let entries = self.range_scan(&[], usize::MAX)?;
+        Ok((entries.len() as u64, entries.iter().map(|(k, v)| (k.len() + v.len()) as u64).sum()))
+    }
     fn flush(&self) -> Result<(), Box<dyn std::error::Error>>;
+    // Writes a key that expires after `ttl`. Expired keys may stay readable
+    // until purge_expired runs, as RocksDB only drops them during compaction.
+    fn put_with_ttl(&self, _key: &[u8], _value: &[u8], _ttl: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        Err(format!("{} does not support TTLs", self.engine_name()).into())
+    }
+    // Removes expired keys so their space can be reclaimed
+    fn purge_expired(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+    // Compacts the whole key space and returns once it is done; a no-op for
+    // engines without a compaction to force
+    fn compact(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+    // Applies a counter increment (little-endian u64) via the engine's merge operator
+    fn merge(&self, _key: &[u8], _value: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        Err(format!("{} does not support merge", self.engine_name()).into())
+    }
+    // Applies all ops atomically where the engine supports it; the default
+    // runs them one by one with no atomicity
+    fn txn(&self, ops: &[Op]) -> Result<(), Box<dyn std::error::Error>> {
+        for op in ops {
+            match op {
+                Op::Put { key, value } => self.put(key, value)?,
+                Op::Delete { key } => self.delete(key)?,
+            }
+        }
+        Ok(())
+    }
     fn engine_name(&self) -> &str;
     fn metrics(&self) -> EngineMetrics;
+    // Directory holding the engine's files, for free-space checks
+    fn data_dir(&self) -> Option<&Path> {
+        None
+    }
+    // How often the engine persists buffered writes on its own, for engines
+    // whose writes only reach disk on a flush
+    fn background_flush_interval(&self) -> Option<Duration> {
+        None
+    }
+    // Key + value bytes written so far, cheap enough to poll during a run
+    fn logical_bytes_written(&self) -> Option<u64> {
+        None
+    }
+    // Version of the underlying library, where the engine can tell
+    fn engine_version(&self) -> Option<String> {
+        None
+    }
+    // Writes a consistent copy of the database to `dest`, which must not exist yet
+    fn checkpoint(&self, _dest: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        Err(format!("{} does not support checkpoints", self.engine_name()).into())
+    }
+    // Whether writes are currently stopped or throttled, for engines that stall
+    fn write_stalled(&self) -> Option<bool> {
+        None
+    }
+    // Summary of the engine's own activity log, for engines that keep one
+    fn log_summary(&self) -> Result<Option<LogSummary>, Box<dyn std::error::Error>> {
+        Ok(None)
+    }
+    // Which of the optional operations above the engine implements
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+}
+
+// What the engine's log says it did: background jobs and write stalls
+#[derive(Debug, Clone, Default)]
+pub struct LogSummary {
+    pub flushes: u64,
+    pub compactions: u64,
+    pub stalls: Vec<StallEvent>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StallEvent {
+    pub timestamp: String, // as the log writes it
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Capabilities {
+    pub merge: bool,
+    pub transactions: bool, // txn is atomic rather than the op-by-op default
+    pub column_families: bool, // num_column_families splits the key space
+    pub checkpoint: bool,
+    pub ttl: bool, // put_with_ttl, once engine_config.ttl is set
 }
 
 #[derive(Debug)]
 pub struct EngineMetrics {
     pub write_amplification: f64,
     pub space_amplification: f64,
+    pub compression_ratio: f64, // logical bytes written / on-disk bytes
+    pub cache_hit_rate: Option<f64>, // None when the engine doesn't expose cache counters
+    pub bloom_false_positive_rate: Option<f64>,
     pub memory_usage_mb: f64,
     pub compaction_stats: (u64, u64), // (bytes_read, bytes_written)
+    pub levels: Vec<LevelStats>, // empty for engines without an LSM
+    pub compaction_pending: Option<bool>,
+    pub column_families: Vec<ColumnFamilyStats>, // empty when all keys share one column family / tree
+    pub physical_bytes_written: Option<u64>, // bytes the engine reports writing to files, e.g. WAL + flush + compaction
+}
+
+#[derive(Debug, Clone)]
+pub struct ColumnFamilyStats {
+    pub name: String,
+    pub keys: u64, // estimated for RocksDB
+    pub size_mb: Option<f64>, // SST bytes; None where the engine can't attribute disk use
+}
+
+#[derive(Debug, Clone)]
+pub struct LevelStats {
+    pub level: usize,
+    pub files: u64,
+    pub size_mb: f64,
+}
+
+// Associative counter merge shared by the engines' merge operators
+fn add_counter<'a>(existing: Option<&[u8]>, operands: impl IntoIterator<Item = &'a [u8]>) -> Vec<u8> {
+    let decode = |bytes: &[u8]| bytes.get(..8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .unwrap_or(0);
+    let total = operands.into_iter()
+        .fold(existing.map(decode).unwrap_or(0), |acc, op| acc.wrapping_add(decode(op)));
+    total.to_le_bytes().to_vec()
+}
+
+// Names of the column families (RocksDB) or trees (Sled) keys are spread
+// across; the first is always the engine's default
+fn partition_names(config: &EngineConfig) -> Vec<String> {
+    let mut names = vec!["default".to_string()];
+    names.extend((1..config.num_column_families.max(1)).map(|i| format!("cf_{}", i)));
+    names
+}
+
+// Stable key -> partition assignment, the same across runs
+fn partition_for(key: &[u8], partitions: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % partitions as u64) as usize
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum CompactionStyle {
+    #[default]
+    Level,
+    Universal,
+    Fifo,
+}
+
+impl std::str::FromStr for CompactionStyle {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "level" => Ok(Self::Level),
+            "universal" => Ok(Self::Universal),
+            "fifo" => Ok(Self::Fifo),
+            other => Err(format!(
+                "unknown compaction style '{}'; expected one of:\n  \
+                 level     - leveled (default): low space amplification, higher write amplification\n  \
+                 universal - size-tiered: lower write amplification, up to 2x space amplification\n  \
+                 fifo      - deletes the oldest files past a size limit: minimal write amplification, loses data",
+                other
+            )),
+        }
+    }
+}
+
+// Key order for RocksDB, which scans and compaction follow. Sled always
+// orders keys bytewise.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum KeyComparator {
+    #[default]
+    Lexicographic,
+    Reverse, // descending bytewise
+    Numeric, // digit runs compare as numbers, so "key_9" sorts before "key_10"
+}
+impl std::str::FromStr for KeyComparator {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lexicographic" => Ok(Self::Lexicographic),
+            "reverse" => Ok(Self::Reverse),
+            "numeric" => Ok(Self::Numeric),
+            other => Err(format!("unknown comparator '{}' (expected lexicographic, reverse or numeric)", other)),
+        }
+    }
+}
+
+impl KeyComparator {
+    pub fn compare(&self, a: &[u8], b: &[u8]) -> std::cmp::Ordering {
+        match self {
+            Self::Lexicographic => a.cmp(b),
+            Self::Reverse => b.cmp(a),
+            Self::Numeric => numeric_cmp(a, b),
+        }
+    }
+}
+
+// Bytewise, except that runs of ASCII digits compare by value. Equal values
+// with different zero padding fall back to the run length, so distinct keys
+// never compare equal.
+fn numeric_cmp(mut a: &[u8], mut b: &[u8]) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    let digits = |s: &[u8]| s.iter().take_while(|c| c.is_ascii_digit()).count();
+    fn trim_zeros(s: &[u8]) -> &[u8] {
+        &s[s.iter().take_while(|&&c| c == b'0').count()..]
+    }
+    loop {
+        match (a.first(), b.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (a_len, b_len) = (digits(a), digits(b));
+                let (a_num, b_num) = (trim_zeros(&a[..a_len]), trim_zeros(&b[..b_len]));
+                let order = a_num.len().cmp(&b_num.len())
+                    .then_with(|| a_num.cmp(b_num))
+                    .then(a_len.cmp(&b_len));
+                if order != Ordering::Equal {
+                    return order;
+                }
+                (a, b) = (&a[a_len..], &b[b_len..]);
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(y);
+                }
+                (a, b) = (&a[1..], &b[1..]);
+            }
+        }
+    }
+}
+
+// How operation types are ordered in the measured phase
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OperationMode {
+    #[default]
+    Mixed, // drawn at random by ratio throughout
+    // write_ratio% of the operations as a write-only phase, then the rest
+    // of the mix without writes, to see each in isolation
+    Phased,
+}
+impl std::str::FromStr for OperationMode {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mixed" => Ok(Self::Mixed),
+            "phased" => Ok(Self::Phased),
+            other => Err(format!("unknown operation mode '{}' (expected mixed or phased)", other)),
+        }
+    }
+}
+
+// How key numbers are turned into key bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum KeyEncoding {
+    #[default]
+    String, // "key_00000042": ordered, and long shared prefixes
+    U64Be, // 8-byte big-endian integer: ordered, compact
+    Uuid, // textual UUID hashed from the number: no locality at all
+    Composite, // 2-byte tenant + 8-byte big-endian sequence, clustered per tenant
+}
+
+impl std::str::FromStr for KeyEncoding {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "string" => Ok(Self::String),
+            "u64_be" => Ok(Self::U64Be),
+            "uuid" => Ok(Self::Uuid),
+            "composite" => Ok(Self::Composite),
+            other => Err(format!("unknown key encoding '{}' (expected string, u64_be, uuid or composite)", other)),
+        }
+    }
+}
+
+// Length keys are padded to; an encoded key longer than its drawn length is
+// left as is. Lengths are derived from the key number, so a key always has
+// the same bytes no matter which operation produces it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum KeySizeDistribution {
+    #[default]
+    Fixed, // the encoding's natural length
+    Uniform { min: usize, max: usize },
+    LogNormal { median: usize, sigma: f64, max: usize }, // samples past max are clamped to it
+}
+
+// Longest key length a distribution may produce; traces store key lengths as a u16
+pub const MAX_KEY_LEN: usize = u16::MAX as usize;
+
+impl KeySizeDistribution {
+    fn len_for(&self, n: u64) -> Option<usize> {
+        // Two independent uniforms in (0, 1] derived from the key number
+        let (a, b) = (mix64(n), mix64(mix64(n)));
+        let unit = |x: u64| ((x >> 11) + 1) as f64 / (1u64 << 53) as f64;
+        let (u1, u2) = (unit(a), unit(b));
+        match *self {
+            Self::Fixed => None,
+            Self::Uniform { min, max } => Some(min + (a % (max.saturating_sub(min) as u64 + 1)) as usize),
+            Self::LogNormal { median, sigma, max } => {
+                // Box-Muller
+                let normal = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+                Some(((median as f64 * (sigma * normal).exp()).round() as usize).min(max))
+            }
+        }
+    }
+    
+    // Longest length the distribution can draw
+    fn max_len(&self) -> Option<usize> {
+        match *self {
+            Self::Fixed => None,
+            Self::Uniform { max, .. } | Self::LogNormal { max, .. } => Some(max),
+        }
+    }
+}
+
+// splitmix64: a cheap, well-mixed bijection on u64
+fn mix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+// FNV-1a over length-prefixed key/value pairs. Unlike DefaultHasher its
+// output is fixed, so fingerprints can be compared across builds.
+struct Fingerprint(u64);
+
+impl Fingerprint {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+    
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    
+    // A missing value hashes differently from an empty one
+    fn add(&mut self, key: &[u8], value: Option<&[u8]>) {
+        self.write(&(key.len() as u64).to_le_bytes());
+        self.write(key);
+        match value {
+            Some(value) => {
+                self.write(&(value.len() as u64).to_le_bytes());
+                self.write(value);
+            }
+            None => self.write(&u64::MAX.to_le_bytes()),
+        }
+    }
+}
+
+// Tuning knobs passed to each engine's constructor
+#[derive(Debug, Clone, Default)]
+pub struct EngineConfig {
+    pub cache_size: Option<usize>, // block cache (RocksDB) / page cache (Sled) bytes; None keeps defaults
+    pub prefix_length: Option<usize>, // fixed-length prefix extractor for RocksDB prefix blooms
+    pub bloom_bits_per_key: Option<f64>, // RocksDB full bloom filter; None disables it
+    pub compaction_style: CompactionStyle, // RocksDB only
+    pub comparator: KeyComparator, // RocksDB only
+    pub disable_wal: bool, // RocksDB skips the WAL, Sled stops its background flush: results are not durable
+    pub min_blob_size: Option<u64>, // RocksDB blob files (key-value separation) for values at least this big
+    pub scan_readahead: Option<usize>, // RocksDB iterator readahead bytes for range scans; None keeps its adaptive default
+    pub num_column_families: usize, // RocksDB column families / Sled trees keys are hashed across; 0 means 1
+    // Every write expires after this long: RocksDB opens as DBWithTTL, Sled
+    // tracks deadlines in a side tree (txn and merge writes don't expire there)
+    pub ttl: Option<Duration>,
 }
 
 pub struct RocksDBEngine {
     db: rocksdb::DB,
+    opts: rocksdb::Options, // kept for statistics tickers
+    column_families: Vec<String>,
+    write_opts: rocksdb::WriteOptions,
+    comparator: KeyComparator,
+    scan_readahead: Option<usize>,
+    ttl: Option<Duration>,
     path: std::path::PathBuf,
     bytes_written: AtomicU64,
 }
 
 impl RocksDBEngine {
-    pub fn new(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+    const NUM_LEVELS: usize = 7;
+    
+    pub fn new(path: &Path, config: &EngineConfig) -> Result<Self, Box<dyn std::error::Error>> {
         let mut opts = rocksdb::Options::default();
         opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
         opts.set_write_buffer_size(64 * 1024 * 1024);
         opts.set_target_file_size_base(64 * 1024 * 1024);
         opts.enable_statistics();
         opts.set_compression_type(rocksdb::DBCompressionType::Lz4);
+        opts.set_merge_operator_associative("counter_add", |_key, existing, operands| {
+            Some(add_counter(existing, operands))
+        });
+        
+        // The name is persisted, so a database can't be reopened with another ordering
+        let comparator = config.comparator;
+        match comparator {
+            KeyComparator::Lexicographic => {}
+            KeyComparator::Reverse => opts.set_comparator("db_bench.reverse", Box::new(move |a, b| comparator.compare(a, b))),
+            KeyComparator::Numeric => opts.set_comparator("db_bench.numeric", Box::new(move |a, b| comparator.compare(a, b))),
+        }
+        
+        opts.set_compaction_style(match config.compaction_style {
+            CompactionStyle::Level => rocksdb::DBCompactionStyle::Level,
+            CompactionStyle::Universal => rocksdb::DBCompactionStyle::Universal,
+            CompactionStyle::Fifo => rocksdb::DBCompactionStyle::Fifo,
+        });
+        
+        // Large values live in blob files, so compaction rewrites only keys and blob references
+        if let Some(min_blob_size) = config.min_blob_size {
+            opts.set_enable_blob_files(true);
+            opts.set_min_blob_size(min_blob_size);
+            opts.set_blob_compression_type(rocksdb::DBCompressionType::Lz4);
+            opts.set_enable_blob_gc(true);
+        }
         
-        let db = rocksdb::DB::open(&opts, path)?;
+        if let Some(prefix_length) = config.prefix_length {
+            opts.set_prefix_extractor(rocksdb::SliceTransform::create_fixed_prefix(prefix_length));
+        }
+        
+        let mut block_opts = rocksdb::BlockBasedOptions::default();
+        if let Some(cache_size) = config.cache_size {
+            block_opts.set_block_cache(&rocksdb::Cache::new_lru_cache(cache_size));
+        }
+        if let Some(bits_per_key) = config.bloom_bits_per_key {
+            block_opts.set_bloom_filter(bits_per_key, false);
+        }
+        opts.set_block_based_table_factory(&block_opts);
+        
+        // Every column family gets the same tuning as the default one
+        let column_families = partition_names(config);
+        let descriptors = column_families.iter()
+            .map(|name| rocksdb::ColumnFamilyDescriptor::new(name, opts.clone()));
+        let db = match config.ttl {
+            Some(ttl) => rocksdb::DB::open_cf_descriptors_with_ttl(&opts, path, descriptors, ttl)?,
+            None => rocksdb::DB::open_cf_descriptors(&opts, path, descriptors)?,
+        };
+        let mut write_opts = rocksdb::WriteOptions::default();
+        write_opts.disable_wal(config.disable_wal);
         Ok(Self { 
             db,
+            opts,
+            column_families,
+            write_opts,
+            comparator,
+            scan_readahead: config.scan_readahead,
+            ttl: config.ttl,
             path: path.to_path_buf(),
             bytes_written: AtomicU64::new(0),
         })
     }
+    
+    fn cf(&self, key: &[u8]) -> Result<&rocksdb::ColumnFamily, Box<dyn std::error::Error>> {
+        let name = &self.column_families[partition_for(key, self.column_families.len())];
+        Ok(self.db.cf_handle(name).ok_or_else(|| format!("column family '{}' is not open", name))?)
+    }
+    
+    fn handles(&self) -> impl Iterator<Item = &rocksdb::ColumnFamily> {
+        self.column_families.iter().filter_map(|name| self.db.cf_handle(name))
+    }
+    
+    // Sums an integer property over every column family
+    fn int_property(&self, name: &str) -> Option<u64> {
+        self.handles().map(|cf| self.db.property_int_value_cf(cf, name).unwrap_or(None)).sum()
+    }
+    
+    fn level_stats(&self) -> Vec<LevelStats> {
+        // rocksdb.levelstats is a small table: "Level Files Size(MB)", a rule, then one row per level
+        let mut sizes = [0.0; Self::NUM_LEVELS];
+        for cf in self.handles() {
+            if let Ok(Some(table)) = self.db.property_value_cf(cf, "rocksdb.levelstats") {
+                for row in table.lines().skip(2) {
+                    let cols: Vec<&str> = row.split_whitespace().collect();
+                    if let [level, _files, size_mb] = cols[..] {
+                        if let (Ok(level), Ok(size_mb)) = (level.parse::<usize>(), size_mb.parse::<f64>()) {
+                            if let Some(size) = sizes.get_mut(level) {
+                                *size += size_mb;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        
+        (0..Self::NUM_LEVELS).map(|level| LevelStats {
+            level,
+            files: self.int_property(&format!("rocksdb.num-files-at-level{}", level)).unwrap_or(0),
+            size_mb: sizes[level],
+        }).collect()
+    }
+    
+    fn column_family_stats(&self) -> Vec<ColumnFamilyStats> {
+        if self.column_families.len() < 2 {
+            return Vec::new();
+        }
+        self.column_families.iter().filter_map(|name| {
+            let cf = self.db.cf_handle(name)?;
+            let property = |p: &str| self.db.property_int_value_cf(cf, p).unwrap_or(None);
+            Some(ColumnFamilyStats {
+                name: name.clone(),
+                keys: property("rocksdb.estimate-num-keys").unwrap_or(0),
+                size_mb: property("rocksdb.total-sst-files-size").map(|bytes| bytes as f64 / 1024.0 / 1024.0),
+            })
+        }).collect()
+    }
 }
 
 impl StorageEngine for RocksDBEngine {
     fn put(&self, key: &[u8], value: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
         self.bytes_written.fetch_add((key.len() + value.len()) as u64, Ordering::Relaxed);
-        self.db.put(key, value)?;
+        self.db.put_cf_opt(self.cf(key)?, key, value, &self.write_opts)?;
         Ok(())
     }
     
     fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
-        Ok(self.db.get(key)?)
+        Ok(self.db.get_cf(self.cf(key)?, key)?)
+    }
+    
+    fn multi_get(&self, keys: &[Vec<u8>]) -> Result<Vec<Option<Vec<u8>>>, Box<dyn std::error::Error>> {
+        let keys = keys.iter()
+            .map(|key| Ok((self.cf(key)?, key)))
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+        let values = self.db.multi_get_cf(keys)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(values)
+    }
+    
+    fn delete(&self, key: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.db.delete_cf_opt(self.cf(key)?, key, &self.write_opts)?;
+        Ok(())
+    }
+    
+    fn range_scan(&self, start: &[u8], limit: usize) -> Result<KvPairs, Box<dyn std::error::Error>> {
+        // Keys are hashed across column families, so take `limit` from each and
+        // merge, reading every family at one snapshot so the merge is one view
+        let snapshot = self.db.snapshot();
+        let mut entries = Vec::new();
+        for cf in self.handles() {
+            // Ignore the prefix extractor so scans may cross prefix boundaries
+            let mut read_opts = rocksdb::ReadOptions::default();
+            read_opts.set_total_order_seek(true);
+            read_opts.set_snapshot(&snapshot);
+            if let Some(readahead) = self.scan_readahead {
+                read_opts.set_readahead_size(readahead);
+            }
+            let iter = self.db.iterator_cf_opt(cf, read_opts, rocksdb::IteratorMode::From(start, rocksdb::Direction::Forward));
+            for entry in iter.take(limit) {
+                let (k, v) = entry?;
+                entries.push((k.to_vec(), v.to_vec()));
+            }
+        }
+        entries.sort_by(|a, b| self.comparator.compare(&a.0, &b.0));
+        entries.truncate(limit);
+        Ok(entries)
+    }
+    
+    fn prefix_scan(&self, prefix: &[u8]) -> Result<KvPairs, Box<dyn std::error::Error>> {
+        // Descending, the keys extending the prefix sort before it, so seek to
+        // the first key past the prefix's range and walk down from there
+        let reverse = self.comparator == KeyComparator::Reverse;
+        let past_range = prefix_successor(prefix);
+        let mut entries = Vec::new();
+        for cf in self.handles() {
+            let iter = if reverse {
+                let mut read_opts = rocksdb::ReadOptions::default();
+                read_opts.set_total_order_seek(true);
+                let mode = match &past_range {
+                    Some(key) => rocksdb::IteratorMode::From(key, rocksdb::Direction::Forward),
+                    None => rocksdb::IteratorMode::Start,
+                };
+                self.db.iterator_cf_opt(cf, read_opts, mode)
+            } else {
+                self.db.prefix_iterator_cf(cf, prefix)
+            };
+            for entry in iter {
+                let (k, v) = entry?;
+                if !k.starts_with(prefix) {
+                    // Descending, the seek can land on past_range itself
+                    if reverse && k.as_ref() > prefix {
+                        continue;
+                    }
+                    break;
+                }
+                entries.push((k.to_vec(), v.to_vec()));
+            }
+        }
+        entries.sort_by(|a, b| self.comparator.compare(&a.0, &b.0));
+        Ok(entries)
+    }
+    
+    // Sequential read: large readahead, and no prefix extractor in the way
+    fn full_scan(&self) -> Result<(u64, u64), Box<dyn std::error::Error>> {
+        let (mut keys, mut bytes) = (0u64, 0u64);
+        for cf in self.handles() {
+            let mut read_opts = rocksdb::ReadOptions::default();
+            read_opts.set_total_order_seek(true);
+            read_opts.set_readahead_size(2 * 1024 * 1024);
+            for entry in self.db.iterator_cf_opt(cf, read_opts, rocksdb::IteratorMode::Start) {
+                let (k, v) = entry?;
+                keys += 1;
+                bytes += (k.len() + v.len()) as u64;
+            }
+        }
+        Ok((keys, bytes))
     }
     
-    fn range_scan(&self, start: &[u8], limit: usize) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Box<dyn std::error::Error>> {
-        let iter = self.db.iterator(rocksdb::IteratorMode::From(start, rocksdb::Direction::Forward));
-        Ok(iter.take(limit).map(|r| {
-            let (k, v) = r.unwrap();
-            (k.to_vec(), v.to_vec())
-        }).collect())
+    // Writes one sorted SST file per column family and ingests it, bypassing
+    // the WAL and memtables entirely
+    fn bulk_load(&self, pairs: KvPairs) -> Result<(), Box<dyn std::error::Error>> {
+        if self.ttl.is_some() {
+            return Err("bulk loading bypasses the timestamps DBWithTTL adds to values; unset engine_config.ttl".into());
+        }
+        let dir = tempfile::tempdir()?;
+        let mut partitions: Vec<KvPairs> = vec![Vec::new(); self.column_families.len()];
+        for (key, value) in pairs {
+            self.bytes_written.fetch_add((key.len() + value.len()) as u64, Ordering::Relaxed);
+            partitions[partition_for(&key, self.column_families.len())].push((key, value));
+        }
+        for (i, mut entries) in partitions.into_iter().enumerate() {
+            if entries.is_empty() {
+                continue;
+            }
+            // SST keys must be strictly increasing; of duplicate keys the last one written wins
+            entries.sort_by(|a, b| self.comparator.compare(&a.0, &b.0));
+            let path = dir.path().join(format!("bulk_{}.sst", i));
+            let mut writer = rocksdb::SstFileWriter::create(&self.opts);
+            writer.open(&path)?;
+            for (j, (key, value)) in entries.iter().enumerate() {
+                if entries.get(j + 1).is_some_and(|next| next.0 == *key) {
+                    continue;
+                }
+                writer.put(key, value)?;
+            }
+            writer.finish()?;
+            let name = &self.column_families[i];
+            let cf = self.db.cf_handle(name).ok_or_else(|| format!("column family '{}' is not open", name))?;
+            self.db.ingest_external_file_cf(cf, vec![&path])?;
+        }
+        Ok(())
     }
     
     fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
-        self.db.flush()?;
+        for cf in self.handles() {
+            self.db.flush_cf(cf)?;
+        }
+        Ok(())
+    }
+    
+    // DBWithTTL has one TTL for the whole database, fixed when it is opened
+    fn put_with_ttl(&self, key: &[u8], value: &[u8], ttl: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        if self.ttl != Some(ttl) {
+            return Err(format!("RocksDB TTL is set at open; engine_config.ttl must be {:?}", ttl).into());
+        }
+        self.put(key, value)
+    }
+    
+    // The TTL compaction filter drops expired keys from whatever it compacts
+    fn purge_expired(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.compact()
+    }
+    
+    fn compact(&self) -> Result<(), Box<dyn std::error::Error>> {
+        for cf in self.handles() {
+            self.db.compact_range_cf(cf, None::<&[u8]>, None::<&[u8]>);
+        }
+        Ok(())
+    }
+    
+    fn merge(&self, key: &[u8], value: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.bytes_written.fetch_add((key.len() + value.len()) as u64, Ordering::Relaxed);
+        self.db.merge_cf_opt(self.cf(key)?, key, value, &self.write_opts)?;
+        Ok(())
+    }
+    
+    fn txn(&self, ops: &[Op]) -> Result<(), Box<dyn std::error::Error>> {
+        let mut batch = rocksdb::WriteBatch::default();
+        for op in ops {
+            match op {
+                Op::Put { key, value } => {
+                    self.bytes_written.fetch_add((key.len() + value.len()) as u64, Ordering::Relaxed);
+                    batch.put_cf(self.cf(key)?, key, value);
+                }
+                Op::Delete { key } => batch.delete_cf(self.cf(key)?, key),
+            }
+        }
+        self.db.write_opt(batch, &self.write_opts)?;
         Ok(())
     }
     
@@ -76,6 +759,68 @@ impl StorageEngine for RocksDBEngine {
         "RocksDB (LSM)"
     }
     
+    fn compare_keys(&self, a: &[u8], b: &[u8]) -> std::cmp::Ordering {
+        self.comparator.compare(a, b)
+    }
+    
+    // Stopped outright (too many L0 files or full memtables) or slowed to the delayed write rate
+    fn write_stalled(&self) -> Option<bool> {
+        let stopped = self.db.property_int_value("rocksdb.is-write-stopped").ok()??;
+        let delayed_rate = self.db.property_int_value("rocksdb.actual-delayed-write-rate").ok()??;
+        Some(stopped > 0 || delayed_rate > 0)
+    }
+    
+    fn data_dir(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+    
+    fn logical_bytes_written(&self) -> Option<u64> {
+        Some(self.bytes_written.load(Ordering::Relaxed))
+    }
+    
+    // Finished jobs come from the structured EVENT_LOG_v1 lines; stalls from
+    // the "Stalling writes because ..." / "Stopping writes because ..." warnings
+    fn log_summary(&self) -> Result<Option<LogSummary>, Box<dyn std::error::Error>> {
+        let log = fs::read_to_string(self.path.join("LOG"))?;
+        let mut summary = LogSummary::default();
+        for line in log.lines() {
+            if let Some(event) = line.split_once("EVENT_LOG_v1").map(|(_, event)| event) {
+                if event.contains("\"event\": \"flush_finished\"") {
+                    summary.flushes += 1;
+                } else if event.contains("\"event\": \"compaction_finished\"") {
+                    summary.compactions += 1;
+                }
+            } else if let Some(start) = line.find("Stalling writes because").or_else(|| line.find("Stopping writes because")) {
+                summary.stalls.push(StallEvent {
+                    timestamp: line.split_whitespace().next().unwrap_or_default().to_string(),
+                    reason: line[start..].to_string(),
+                });
+            }
+        }
+        Ok(Some(summary))
+    }
+    
+    // Hard-links the live SST files and copies the rest, so cost grows with file count, not data size
+    fn checkpoint(&self, dest: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        rocksdb::checkpoint::Checkpoint::new(&self.db)?.create_checkpoint(dest)?;
+        Ok(())
+    }
+    
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { merge: true, transactions: true, column_families: true, checkpoint: true, ttl: true }
+    }
+    
+    // The library doesn't expose its version, but every OPTIONS file it
+    // writes records it as "rocksdb_version=X.Y.Z"
+    fn engine_version(&self) -> Option<String> {
+        fs::read_dir(&self.path).ok()?
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("OPTIONS-"))
+            .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+            .find_map(|options| options.lines()
+                .find_map(|line| line.trim().strip_prefix("rocksdb_version=").map(str::to_string)))
+    }
+    
     fn metrics(&self) -> EngineMetrics {
         let bytes_written = self.bytes_written.load(Ordering::Relaxed);
         
@@ -99,280 +844,3388 @@ impl StorageEngine for RocksDBEngine {
             dir_size as f64 / bytes_written as f64
         } else { 1.0 };
         
-        let mem_usage = self.db.property_int_value("rocksdb.cur-size-all-mem-tables")
-            .unwrap_or(Some(0)).unwrap_or(0) as f64 / 1024.0 / 1024.0;
+        let live_size = self.int_property("rocksdb.estimate-live-data-size").unwrap_or(dir_size);
+        let compression_ratio = if live_size > 0 {
+            bytes_written as f64 / live_size as f64
+        } else { 1.0 };
+        
+        let cache_hits = self.opts.get_ticker_count(rocksdb::statistics::Ticker::BlockCacheHit);
+        let cache_misses = self.opts.get_ticker_count(rocksdb::statistics::Ticker::BlockCacheMiss);
+        let cache_hit_rate = if cache_hits + cache_misses > 0 {
+            Some(cache_hits as f64 / (cache_hits + cache_misses) as f64)
+        } else { None };
+        
+        // Lookups the filter ruled out vs. ones it let through for absent keys
+        let bloom_useful = self.opts.get_ticker_count(rocksdb::statistics::Ticker::BloomFilterUseful);
+        let bloom_positive = self.opts.get_ticker_count(rocksdb::statistics::Ticker::BloomFilterFullPositive);
+        let bloom_true_positive = self.opts.get_ticker_count(rocksdb::statistics::Ticker::BloomFilterFullTruePositive);
+        let bloom_false_positive = bloom_positive.saturating_sub(bloom_true_positive);
+        let bloom_false_positive_rate = if bloom_useful + bloom_false_positive > 0 {
+            Some(bloom_false_positive as f64 / (bloom_useful + bloom_false_positive) as f64)
+        } else { None };
+        
+        let mem_usage = self.int_property("rocksdb.cur-size-all-mem-tables").unwrap_or(0) as f64 / 1024.0 / 1024.0;
         
         EngineMetrics {
             write_amplification: write_amp,
             space_amplification: space_amp,
+            compression_ratio,
+            cache_hit_rate,
+            bloom_false_positive_rate,
             memory_usage_mb: mem_usage,
             compaction_stats: (compact_read, compact_write),
+            levels: self.level_stats(),
+            compaction_pending: self.int_property("rocksdb.compaction-pending").map(|pending| pending > 0),
+            column_families: self.column_family_stats(),
+            physical_bytes_written: Some(
+                self.opts.get_ticker_count(rocksdb::statistics::Ticker::WalFileBytes)
+                    + self.opts.get_ticker_count(rocksdb::statistics::Ticker::FlushWriteBytes)
+                    + self.opts.get_ticker_count(rocksdb::statistics::Ticker::CompactWriteBytes),
+            ),
         }
     }
 }
 
 pub struct SledEngine {
     db: sled::Db,
+    trees: Vec<sled::Tree>, // the default tree first; keys are hashed across them
+    tree_names: Vec<String>,
+    expiry: Option<sled::Tree>, // key -> deadline in Unix millis (u64 BE), when a TTL is configured
+    ttl: Option<Duration>,
+    flush_every: Option<Duration>,
     path: std::path::PathBuf,
+    cache_capacity: u64,
     bytes_written: AtomicU64,
 }
 
 impl SledEngine {
-    pub fn new(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
-        let config = sled::Config::new()
+    pub fn new(path: &Path, config: &EngineConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let cache_capacity = config.cache_size.unwrap_or(128 * 1024 * 1024) as u64;
+        let flush_every = (!config.disable_wal).then(|| Duration::from_millis(500));
+        let sled_config = sled::Config::new()
             .path(path)
-            .cache_capacity(128 * 1024 * 1024);
-            
-        let db = config.open()?;
+            .cache_capacity(cache_capacity)
+            .flush_every_ms(flush_every.map(|every| every.as_millis() as u64));
+
+        let db = sled_config.open()?;
+        let tree_names = partition_names(config);
+        let mut trees = vec![(*db).clone()];
+        for name in &tree_names[1..] {
+            trees.push(db.open_tree(name)?);
+        }
+        for tree in &trees {
+            tree.set_merge_operator(|_key: &[u8], existing: Option<&[u8]>, operand: &[u8]| {
+                Some(add_counter(existing, [operand]))
+            });
+        }
+        let expiry = match config.ttl {
+            Some(_) => Some(db.open_tree("__ttl_expiry")?),
+            None => None,
+        };
         Ok(Self { 
             db,
+            trees,
+            tree_names,
+            expiry,
+            ttl: config.ttl,
+            flush_every,
             path: path.to_path_buf(),
+            cache_capacity,
             bytes_written: AtomicU64::new(0),
         })
     }
 }
 
+impl SledEngine {
+    fn tree(&self, key: &[u8]) -> &sled::Tree {
+        &self.trees[partition_for(key, self.trees.len())]
+    }
+    
+    fn expired(&self, key: &[u8]) -> Result<bool, Box<dyn std::error::Error>> {
+        match &self.expiry {
+            Some(expiry) => Ok(expiry.get(key)?.is_some_and(|deadline| deadline_millis(&deadline) <= unix_millis())),
+            None => Ok(false),
+        }
+    }
+
+    // Up to `limit` entries from one tree's iterator, skipping expired keys
+    fn live_entries(&self, iter: sled::Iter, limit: usize) -> Result<KvPairs, Box<dyn std::error::Error>> {
+        let mut entries = KvPairs::new();
+        for entry in iter {
+            if entries.len() == limit {
+                break;
+            }
+            let (k, v) = entry?;
+            if !self.expired(&k)? {
+                entries.push((k.to_vec(), v.to_vec()));
+            }
+        }
+        Ok(entries)
+    }
+}
+
 impl StorageEngine for SledEngine {
     fn put(&self, key: &[u8], value: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(ttl) = self.ttl {
+            return self.put_with_ttl(key, value, ttl);
+        }
         self.bytes_written.fetch_add((key.len() + value.len()) as u64, Ordering::Relaxed);
-        self.db.insert(key, value)?;
+        self.tree(key).insert(key, value)?;
         Ok(())
     }
     
     fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
-        Ok(self.db.get(key)?.map(|v| v.to_vec()))
+        if self.expired(key)? {
+            return Ok(None);
+        }
+        Ok(self.tree(key).get(key)?.map(|v| v.to_vec()))
     }
     
-    fn range_scan(&self, start: &[u8], limit: usize) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Box<dyn std::error::Error>> {
-        Ok(self.db.range(start..)
-            .take(limit)
-            .filter_map(Result::ok)
-            .map(|(k, v)| (k.to_vec(), v.to_vec()))
-            .collect())
+    fn delete(&self, key: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.tree(key).remove(key)?;
+        if let Some(expiry) = &self.expiry {
+            expiry.remove(key)?;
+        }
+        Ok(())
     }
     
-    fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
-        self.db.flush()?;
+    fn put_with_ttl(&self, key: &[u8], value: &[u8], ttl: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        let expiry = self.expiry.as_ref().ok_or("Sled only tracks expiry once engine_config.ttl is set")?;
+        self.bytes_written.fetch_add((key.len() + value.len()) as u64, Ordering::Relaxed);
+        self.tree(key).insert(key, value)?;
+        expiry.insert(key, &(unix_millis() + ttl.as_millis() as u64).to_be_bytes())?;
         Ok(())
     }
     
-    fn engine_name(&self) -> &str {
-        "Sled (B-Tree)"
+    fn purge_expired(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(expiry) = &self.expiry {
+            let now = unix_millis();
+            for entry in expiry.iter() {
+                let (key, deadline) = entry?;
+                if deadline_millis(&deadline) <= now {
+                    self.tree(&key).remove(&key)?;
+                    expiry.remove(&key)?;
+                }
+            }
+        }
+        Ok(())
     }
     
-    fn metrics(&self) -> EngineMetrics {
-        let bytes_written = self.bytes_written.load(Ordering::Relaxed);
-        let page_size = 8192;
-        let page_rewrites = (bytes_written / page_size) * page_size * 10;
-        
-        let write_amp = if bytes_written > 0 {
-            (bytes_written + page_rewrites) as f64 / bytes_written as f64
-        } else { 1.0 };
-        
-        let dir_size = fs_size(&self.path).unwrap_or(0);
-        let space_amp = if bytes_written > 0 {
-            dir_size as f64 / bytes_written as f64
-        } else { 1.0 };
-        
-        EngineMetrics {
-            write_amplification: write_amp,
-            space_amplification: space_amp,
-            memory_usage_mb: 128.0, // cache capacity
-            compaction_stats: (dir_size, page_rewrites),
+    fn range_scan(&self, start: &[u8], limit: usize) -> Result<KvPairs, Box<dyn std::error::Error>> {
+        // Keys are hashed across trees, so take `limit` from each and merge
+        let mut entries = KvPairs::new();
+        for tree in &self.trees {
+            entries.extend(self.live_entries(tree.range(start..), limit)?);
         }
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries.truncate(limit);
+        Ok(entries)
     }
-}
-
-fn fs_size(path: &Path) -> Result<u64, Box<dyn std::error::Error>> {
-    let mut size = 0u64;
-    for entry in fs::read_dir(path)? {
-        if let Ok(entry) = entry {
-            if let Ok(metadata) = entry.metadata() {
-                size += metadata.len();
-            }
+    
+    fn prefix_scan(&self, prefix: &[u8]) -> Result<KvPairs, Box<dyn std::error::Error>> {
+        let mut entries = KvPairs::new();
+        for tree in &self.trees {
+            entries.extend(self.live_entries(tree.scan_prefix(prefix), usize::MAX)?);
         }
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(entries)
     }
-    Ok(size)
-}
-
-#[derive(Debug)]
+    
+    // sled has no bulk path; one batch per tree at least skips per-key overhead
+    fn bulk_load(&self, pairs: KvPairs) -> Result<(), Box<dyn std::error::Error>> {
+        if self.ttl.is_some() {
+            for (key, value) in pairs {
+                self.put(&key, &value)?;
+            }
+            return Ok(());
+        }
+        let mut batches: Vec<sled::Batch> = self.trees.iter().map(|_| sled::Batch::default()).collect();
+        for (key, value) in pairs {
+            self.bytes_written.fetch_add((key.len() + value.len()) as u64, Ordering::Relaxed);
+            batches[partition_for(&key, self.trees.len())].insert(key, value);
+        }
+        for (tree, batch) in self.trees.iter().zip(batches) {
+            tree.apply_batch(batch)?;
+        }
+        Ok(())
+    }
+    
+    fn full_scan(&self) -> Result<(u64, u64), Box<dyn std::error::Error>> {
+        let (mut keys, mut bytes) = (0u64, 0u64);
+        for tree in &self.trees {
+            for entry in tree.iter() {
+                let (k, v) = entry?;
+                if self.expired(&k)? {
+                    continue;
+                }
+                keys += 1;
+                bytes += (k.len() + v.len()) as u64;
+            }
+        }
+        Ok((keys, bytes))
+    }
+    
+    fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.db.flush()?;
+        Ok(())
+    }
+    
+    fn merge(&self, key: &[u8], value: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.bytes_written.fetch_add((key.len() + value.len()) as u64, Ordering::Relaxed);
+        self.tree(key).merge(key, value)?;
+        Ok(())
+    }
+    
+    fn txn(&self, ops: &[Op]) -> Result<(), Box<dyn std::error::Error>> {
+        // A batch only covers one tree; spanning several needs a transaction
+        if self.trees.len() > 1 {
+            for op in ops {
+                if let Op::Put { key, value } = op {
+                    self.bytes_written.fetch_add((key.len() + value.len()) as u64, Ordering::Relaxed);
+                }
+            }
+            self.trees.as_slice().transaction(|trees| -> sled::transaction::ConflictableTransactionResult<(), sled::Error> {
+                for op in ops {
+                    match op {
+                        Op::Put { key, value } => {
+                            trees[partition_for(key, trees.len())].insert(key.as_slice(), value.as_slice())?;
+                        }
+                        Op::Delete { key } => {
+                            trees[partition_for(key, trees.len())].remove(key.as_slice())?;
+                        }
+                    }
+                }
+                Ok(())
+            })?;
+            return Ok(());
+        }
+        
+        let mut batch = sled::Batch::default();
+        for op in ops {
+            match op {
+                Op::Put { key, value } => {
+                    self.bytes_written.fetch_add((key.len() + value.len()) as u64, Ordering::Relaxed);
+                    batch.insert(key.as_slice(), value.as_slice());
+                }
+                Op::Delete { key } => batch.remove(key.as_slice()),
+            }
+        }
+        self.db.apply_batch(batch)?;
+        Ok(())
+    }
+    
+    fn engine_name(&self) -> &str {
+        "Sled (B-Tree)"
+    }
+    
+    fn data_dir(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+    
+    fn background_flush_interval(&self) -> Option<Duration> {
+        self.flush_every
+    }
+    
+    fn logical_bytes_written(&self) -> Option<u64> {
+        Some(self.bytes_written.load(Ordering::Relaxed))
+    }
+    
+    // sled has no native checkpoint; export every tree and import into a new database
+    fn checkpoint(&self, dest: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let copy = sled::open(dest)?;
+        copy.import(self.db.export());
+        copy.flush()?;
+        Ok(())
+    }
+    
+    // Column families map to sled trees
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { merge: true, transactions: true, column_families: true, checkpoint: true, ttl: true }
+    }
+    
+    // The conf file only holds the on-disk format version, so report the
+    // exact crate version Cargo.toml pins, as build.rs read it
+    fn engine_version(&self) -> Option<String> {
+        Some(env!("SLED_VERSION").to_string())
+    }
+    
+    fn metrics(&self) -> EngineMetrics {
+        let bytes_written = self.bytes_written.load(Ordering::Relaxed);
+        let page_size = 8192;
+        let page_rewrites = (bytes_written / page_size) * page_size * 10;
+        
+        let write_amp = if bytes_written > 0 {
+            (bytes_written + page_rewrites) as f64 / bytes_written as f64
+        } else { 1.0 };
+        
+        let dir_size = fs_size(&self.path).unwrap_or(0);
+        let space_amp = if bytes_written > 0 {
+            dir_size as f64 / bytes_written as f64
+        } else { 1.0 };
+        
+        let disk_size = self.db.size_on_disk().unwrap_or(dir_size);
+        let compression_ratio = if disk_size > 0 {
+            bytes_written as f64 / disk_size as f64
+        } else { 1.0 };
+        
+        EngineMetrics {
+            write_amplification: write_amp,
+            space_amplification: space_amp,
+            compression_ratio,
+            cache_hit_rate: None, // sled only tracks cache hits behind its metrics feature
+            bloom_false_positive_rate: None,
+            memory_usage_mb: self.cache_capacity as f64 / 1024.0 / 1024.0, // cache capacity
+            compaction_stats: (dir_size, page_rewrites),
+            levels: Vec::new(),
+            compaction_pending: None,
+            column_families: if self.trees.len() > 1 {
+                self.tree_names.iter().zip(&self.trees).map(|(name, tree)| ColumnFamilyStats {
+                    name: name.clone(),
+                    keys: tree.len() as u64,
+                    size_mb: None,
+                }).collect()
+            } else {
+                Vec::new()
+            },
+            physical_bytes_written: None,
+        }
+    }
+}
+
+pub type EngineConstructor = fn(&Path, &EngineConfig) -> Result<Arc<dyn StorageEngine>, Box<dyn std::error::Error>>;
+
+pub struct EngineEntry {
+    pub name: &'static str,
+    pub open: EngineConstructor,
+}
+
+// Every engine the benchmark knows about; adding an engine only means adding it here
+pub const ENGINES: &[EngineEntry] = &[
+    EngineEntry { name: "rocksdb", open: |path, config| Ok(Arc::new(RocksDBEngine::new(path, config)?)) },
+    EngineEntry { name: "sled", open: |path, config| Ok(Arc::new(SledEngine::new(path, config)?)) },
+];
+
+pub fn create_engine(name: &str, path: &Path, config: &EngineConfig) -> Result<Arc<dyn StorageEngine>, Box<dyn std::error::Error>> {
+    let entry = ENGINES.iter().find(|e| e.name == name).ok_or_else(|| {
+        let known: Vec<_> = ENGINES.iter().map(|e| e.name).collect();
+        format!("unknown engine '{}' (expected one of: {})", name, known.join(", "))
+    })?;
+    (entry.open)(path, config)
+}
+
+// Opens each registered engine on a scratch directory and prints its
+// version and which optional operations it supports
+pub fn list_engines() -> Result<(), Box<dyn std::error::Error>> {
+    let yes_no = |supported: bool| if supported { "yes" } else { "no" };
+    println!("| Engine | Version | Merge | Transactions | Column Families | Checkpoint | TTL |");
+    println!("|--------|---------|-------|--------------|-----------------|------------|-----|");
+    for entry in ENGINES {
+        let dir = tempfile::tempdir()?;
+        let engine = (entry.open)(dir.path(), &EngineConfig::default())?;
+        let caps = engine.capabilities();
+        println!("| {} | {} | {} | {} | {} | {} | {} |",
+            entry.name,
+            engine.engine_version().as_deref().unwrap_or("unknown"),
+            yes_no(caps.merge),
+            yes_no(caps.transactions),
+            yes_no(caps.column_families),
+            yes_no(caps.checkpoint),
+            yes_no(caps.ttl)
+        );
+    }
+    Ok(())
+}
+
+// Runs one benchmark against a fresh temporary database for the named engine
+pub fn run_workload(benchmark: &Benchmark, engine_name: &str) -> Result<BenchmarkResult, Box<dyn std::error::Error>> {
+    benchmark.run_repeated(|path, config| create_engine(engine_name, path, config))
+}
+
+// Total size of the files under `path`, including subdirectories such as
+// sled's blobs/. Entries that vanish mid-walk (compaction) are skipped.
+fn fs_size(path: &Path) -> Result<u64, Box<dyn std::error::Error>> {
+    let mut size = 0u64;
+    for entry in fs::read_dir(path)?.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            size += fs_size(&entry.path()).unwrap_or(0);
+        } else {
+            size += metadata.len();
+        }
+    }
+    Ok(size)
+}
+
+// The smallest key greater than every key starting with `prefix`, or None
+// when the prefix is all 0xFF and no such key exists
+fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let last = prefix.iter().rposition(|&b| b != 0xFF)?;
+    let mut successor = prefix[..=last].to_vec();
+    successor[last] += 1;
+    Some(successor)
+}
+
+// Copies a directory tree file by file, as a crash would leave it; files
+// the engine deletes mid-copy are skipped
+fn copy_dir(src: &Path, dest: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        let copied = if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)
+        } else {
+            fs::copy(entry.path(), &target).map(|_| ()).map_err(Into::into)
+        };
+        match copied {
+            Err(e) if e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound) => {}
+            result => result?,
+        }
+    }
+    Ok(())
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    needle.is_empty() || haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+}
+
+fn deadline_millis(encoded: &[u8]) -> u64 {
+    encoded.try_into().map_or(u64::MAX, u64::from_be_bytes)
+}
+
+// CPU time consumed by the calling thread so far. Excludes the engine's own
+// background threads (compaction, flushing).
+#[cfg(unix)]
+fn thread_cpu_time() -> Option<Duration> {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: ts is a valid timespec for clock_gettime to write into
+    if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) } != 0 {
+        return None;
+    }
+    Some(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+}
+
+#[cfg(not(unix))]
+fn thread_cpu_time() -> Option<Duration> {
+    None
+}
+
+// Bytes this process has caused to be written to storage, from /proc/self/io (Linux only)
+fn process_disk_bytes_written() -> Option<u64> {
+    let io = fs::read_to_string("/proc/self/io").ok()?;
+    io.lines()
+        .find_map(|line| line.strip_prefix("write_bytes:"))
+        .and_then(|bytes| bytes.trim().parse().ok())
+}
+
+// Open file descriptors in this process, from /proc/self/fd (Linux only)
+fn process_open_fds() -> Option<u64> {
+    Some(fs::read_dir("/proc/self/fd").ok()?.count() as u64)
+}
+
+// Threads in this process, from /proc/self/status (Linux only)
+fn process_threads() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status.lines()
+        .find_map(|line| line.strip_prefix("Threads:"))
+        .and_then(|threads| threads.trim().parse().ok())
+}
+
+// Records a latency in microseconds, growing the histogram if it auto-resizes
+// and otherwise clamping to its highest trackable value. True if clamped.
+fn record_latency(hist: &mut Histogram<u64>, latency: Duration) -> bool {
+    let micros = latency.as_micros() as u64;
+    if hist.record(micros).is_ok() {
+        return false;
+    }
+    hist.saturating_record(micros);
+    true
+}
+
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Default)]
+struct Samples {
+    peak_space_amplification: f64,
+    stall_samples: Option<u64>, // samples that caught writes stalled; None if the engine can't tell
+    stalls: u64, // unstalled -> stalled transitions
+    peak_open_fds: Option<u64>,
+    peak_threads: Option<u64>,
+}
+
+// Polls engine state on a background thread for the duration of a run
+struct Sampler {
+    stop: Arc<AtomicBool>,
+    disk_low: Arc<AtomicBool>,
+    over_budget: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<Samples>>,
+}
+
+impl Sampler {
+    fn spawn(engine: Arc<dyn StorageEngine>, min_free_disk: Option<u64>, max_dataset_bytes: Option<u64>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let disk_low = Arc::new(AtomicBool::new(false));
+        let over_budget = Arc::new(AtomicBool::new(false));
+        let (stop_flag, disk_low_flag, over_budget_flag) = (stop.clone(), disk_low.clone(), over_budget.clone());
+        let handle = thread::spawn(move || {
+            let mut samples = Samples::default();
+            let mut was_stalled = false;
+            while !stop_flag.load(Ordering::Relaxed) {
+                // Only cheap gauges here: the full metrics() can walk whole
+                // trees or every level and would compete with the workload
+                let dataset_bytes = engine.data_dir().and_then(|dir| fs_size(dir).ok());
+                if let (Some(size), Some(logical)) = (dataset_bytes, engine.logical_bytes_written()) {
+                    if logical > 0 {
+                        samples.peak_space_amplification = samples.peak_space_amplification.max(size as f64 / logical as f64);
+                    }
+                }
+                if let Some(stalled) = engine.write_stalled() {
+                    let stall_samples = samples.stall_samples.get_or_insert(0);
+                    if stalled {
+                        *stall_samples += 1;
+                        if !was_stalled {
+                            samples.stalls += 1;
+                        }
+                    }
+                    was_stalled = stalled;
+                }
+                samples.peak_open_fds = samples.peak_open_fds.max(process_open_fds());
+                samples.peak_threads = samples.peak_threads.max(process_threads());
+                if let (Some(min), Some(dir)) = (min_free_disk, engine.data_dir()) {
+                    if fs2::available_space(dir).is_ok_and(|free| free < min) {
+                        disk_low_flag.store(true, Ordering::Relaxed);
+                    }
+                }
+                if let (Some(max), Some(size)) = (max_dataset_bytes, dataset_bytes) {
+                    if size > max {
+                        over_budget_flag.store(true, Ordering::Relaxed);
+                    }
+                }
+                thread::sleep(SAMPLE_INTERVAL);
+            }
+            samples
+        });
+        Self { stop, disk_low, over_budget, handle: Some(handle) }
+    }
+    
+    // Set once free space on the engine's disk has dropped below the limit
+    fn disk_low(&self) -> bool {
+        self.disk_low.load(Ordering::Relaxed)
+    }
+    
+    // Set once the engine's data directory has grown past max_dataset_bytes
+    fn over_budget(&self) -> bool {
+        self.over_budget.load(Ordering::Relaxed)
+    }
+    
+    fn finish(mut self) -> Samples {
+        self.stop.store(true, Ordering::Relaxed);
+        self.handle.take().unwrap().join().unwrap_or_default()
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// Checkpoints the engine every `interval` on a background thread, so the
+// workload runs against the checkpoint as it would in production
+struct Checkpointer {
+    stop: Arc<AtomicBool>,
+    active: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<Result<Vec<Duration>, String>>>,
+}
+
+impl Checkpointer {
+    fn spawn(engine: Arc<dyn StorageEngine>, interval: Duration) -> Result<Self, Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let active = Arc::new(AtomicBool::new(false));
+        let (stop_flag, active_flag) = (stop.clone(), active.clone());
+        let handle = thread::spawn(move || {
+            let mut durations = Vec::new();
+            let mut next = Instant::now() + interval;
+            while !stop_flag.load(Ordering::Relaxed) {
+                if Instant::now() >= next {
+                    let dest = dir.path().join(format!("checkpoint_{}", durations.len()));
+                    active_flag.store(true, Ordering::Relaxed);
+                    let checkpoint_start = Instant::now();
+                    let result = engine.checkpoint(&dest);
+                    durations.push(checkpoint_start.elapsed());
+                    active_flag.store(false, Ordering::Relaxed);
+                    result.map_err(|e| e.to_string())?;
+                    // Only the cost of taking the checkpoint matters; don't let copies fill the disk
+                    let _ = fs::remove_dir_all(&dest);
+                    next = Instant::now() + interval;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            Ok(durations)
+        });
+        Ok(Self { stop, active, handle: Some(handle) })
+    }
+    
+    // Set while a checkpoint is being taken
+    fn active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+    
+    fn finish(mut self) -> Result<Vec<Duration>, Box<dyn std::error::Error>> {
+        self.stop.store(true, Ordering::Relaxed);
+        let durations = self.handle.take().unwrap().join().map_err(|_| "checkpoint thread panicked")??;
+        Ok(durations)
+    }
+}
+
+impl Drop for Checkpointer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+const STREAM_INTERVAL: Duration = Duration::from_secs(1);
+
+// Appends one JSON line per STREAM_INTERVAL of the measured phase, so a run
+// can be followed live with `tail -f`
+struct StreamOutput {
+    out: fs::File,
+    engine: String,
+    hist: Histogram<u64>,
+    start: Instant,
+    window_start: Instant,
+}
+
+impl StreamOutput {
+    fn open(path: &Path, engine: &str, hist: Histogram<u64>) -> std::io::Result<Self> {
+        let out = fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let now = Instant::now();
+        Ok(Self { out, engine: engine.to_string(), hist, start: now, window_start: now })
+    }
+    
+    fn record(&mut self, latency: Duration) -> std::io::Result<()> {
+        record_latency(&mut self.hist, latency);
+        if self.window_start.elapsed() >= STREAM_INTERVAL {
+            self.emit()?;
+        }
+        Ok(())
+    }
+    
+    // Writes the current window, if it saw any operations, and starts the next
+    fn emit(&mut self) -> std::io::Result<()> {
+        if !self.hist.is_empty() {
+            // One write per line so a reader never sees half of one
+            let line = format!(
+                "{{\"engine\":{},\"elapsed_secs\":{:.3},\"operations\":{},\"throughput\":{:.1},\"p50_ms\":{:.3},\"p99_ms\":{:.3}}}\n",
+                json::string(&self.engine),
+                self.start.elapsed().as_secs_f64(),
+                self.hist.len(),
+                self.hist.len() as f64 / self.window_start.elapsed().as_secs_f64(),
+                self.hist.value_at_percentile(50.0) as f64 / 1000.0,
+                self.hist.value_at_percentile(99.0) as f64 / 1000.0,
+            );
+            self.out.write_all(line.as_bytes())?;
+        }
+        self.hist.reset();
+        self.window_start = Instant::now();
+        Ok(())
+    }
+}
+
+// Slowest rate a RateLimiter accepts; its waits of up to 1/rate seconds
+// must fit in a Duration
+pub const MIN_RATE: f64 = 0.001;
+
+// Token bucket pacing operations to a fixed rate. Holds up to a tenth of a
+// second of tokens, so a stall can be made up with a short burst but the
+// rate doesn't drift above the target.
+struct RateLimiter {
+    rate: f64, // tokens per second
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    fn new(rate: f64) -> Self {
+        let capacity = (rate / 10.0).max(1.0);
+        Self { rate, capacity, tokens: 0.0, last: Instant::now() }
+    }
+    
+    // Blocks until a token is available, then takes it
+    fn acquire(&mut self) {
+        let now = Instant::now();
+        self.tokens = (self.tokens + now.duration_since(self.last).as_secs_f64() * self.rate).min(self.capacity);
+        self.last = now;
+        if self.tokens < 1.0 {
+            thread::sleep(Duration::from_secs_f64((1.0 - self.tokens) / self.rate));
+            self.tokens = 1.0;
+            self.last = Instant::now();
+        }
+        self.tokens -= 1.0;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopReason {
+    OperationCount,
+    Duration,
+    DiskSpace, // free space fell below min_free_disk
+    DatasetSize, // the data directory grew past max_dataset_bytes
+    Converged, // throughput settled within the convergence threshold
+}
+
+impl StopReason {
+    // Stopped to protect the disk, so nothing more should be written
+    pub fn storage_limited(self) -> bool {
+        matches!(self, StopReason::DiskSpace | StopReason::DatasetSize)
+    }
+}
+
+// Ends a run once throughput over the last `windows` windows of length
+// `window` has a coefficient of variation below `max_cv`. The operation
+// count and max_duration still cap the run.
+#[derive(Debug, Clone, Copy)]
+pub struct Convergence {
+    pub window: Duration,
+    pub windows: usize,
+    pub max_cv: f64,
+}
+
+// Shape of the offered load over time
+#[derive(Debug, Clone, Copy, Default)]
+pub enum LoadPattern {
+    #[default]
+    Steady, // flat out, or at rate_limit when set
+    // Bursts at peak_rate, each followed by an idle period with no
+    // operations in which background work like compaction can catch up
+    Burst { peak_rate: f64, burst: Duration, idle: Duration },
+}
+
+#[derive(Debug)]
 pub struct BenchmarkResult {
     pub engine_name: String,
+    pub engine_version: Option<String>,
+    pub seed: u64,
+    pub operations: u64, // measured operations completed
     pub throughput: f64,
+    pub throughput_excluding_flush: f64, // with the periodic flushes' time taken out of the elapsed time
+    pub flush_secs: f64, // total time in periodic flushes
+    pub write_throughput: f64,
+    pub read_throughput: f64,
+    pub scan_throughput: f64,
+    pub merge_throughput: f64,
+    pub prefix_scan_throughput: f64,
+    pub txn_throughput: f64,
+    pub multi_get_throughput: f64, // batches per second
+    pub load: Option<LoadPhase>, // None when loading was skipped
+    pub dataset_fingerprint: u64, // of the prepopulated key/value pairs
+    pub stop_reason: StopReason,
+    pub converged_after_secs: Option<f64>,
     pub write_p99_ms: f64,
+    pub insert_p99_ms: f64, // writes that created a key; only split out when update_ratio is set
+    pub update_p99_ms: f64,
     pub read_p99_ms: f64,
+    pub read_hit_p99_ms: f64,
+    pub read_miss_p99_ms: f64, // reads that found no key; bloom filters make these cheap on LSMs
     pub scan_p99_ms: f64,
+    pub merge_p99_ms: f64,
+    pub prefix_scan_p99_ms: f64,
+    pub txn_commit_p99_ms: f64,
+    pub multi_get_p99_ms: f64, // whole batch
+    pub saturated_samples: u64, // latencies above max_latency, recorded as max_latency
+    pub cpu_time: Option<CpuTime>, // set when measure_cpu_time is and the platform supports it
+    // Base64 of the V2 (deflate) serialized histograms, in microseconds
+    pub write_histogram: String,
+    pub read_histogram: String,
+    pub scan_histogram: String,
+    pub latency_windows: Vec<LatencyWindow>, // empty unless latency_window is set
+    pub latency_scaling: Option<LatencyScaling>, // from latency_windows, once there are enough
+    pub phases: Vec<PhaseStats>, // empty unless operation_mode is Phased
+    pub bursts: Vec<BurstStats>, // empty unless load_pattern is Burst
+    pub write_size_buckets: Vec<SizeBucketLatency>, // one entry per VALUE_SIZE_BUCKETS bucket
+    pub scan_selectivity: Option<ScanSelectivity>, // set when scan_filter is
+    pub consistency_violations: Option<u64>,
+    pub reader_writer: Option<ReaderWriterRun>, // set when writer_threads or reader_threads is
+    pub staleness: Option<StalenessCheck>,
+    pub full_scan: Option<FullScanStats>,
+    pub full_compaction: Option<FullCompaction>, // set when compact_before_run is
+    pub engine_log: Option<LogSummary>, // set when parse_engine_log is and the engine keeps a log
+    pub checkpoints: Option<CheckpointStats>, // set when checkpoint_interval is
+    pub ttl: Option<TtlCheck>, // set when engine_config.ttl is
+    pub peak_space_amplification: f64, // highest value sampled during the run
+    // Process-wide peaks sampled during the run, so they include the
+    // benchmark's own threads and files; None off Linux
+    pub peak_open_fds: Option<u64>,
+    pub peak_threads: Option<u64>,
+    pub write_stalls: Option<WriteStalls>, // None for engines that don't stall writes
+    pub disk_bytes_written: Option<u64>,
+    pub open_latency_ms: Option<f64>,
+    pub recovery_ms: Option<f64>, // reopen time after the run, when measured
+    pub durability: Option<DurabilityCheck>,
+    pub summary: Option<RunSummary>, // set when the benchmark was repeated
     pub metrics: EngineMetrics,
 }
 
-pub struct Benchmark {
-    write_ratio: u32,
-    scan_ratio: u32,
-    value_size: usize,
-    num_operations: u64,
-    scan_length: usize,
-}
+#[derive(Debug, Clone, Copy)]
+pub struct Stats {
+    pub mean: f64,
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[f64]) -> Self {
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        // Sample standard deviation; a single run has no spread
+        let stddev = if samples.len() > 1 {
+            (samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else { 0.0 };
+        Self {
+            mean,
+            stddev,
+            min: samples.iter().cloned().fold(f64::INFINITY, f64::min),
+            max: samples.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+// Thread CPU time per operation, across all operation types. The rest of
+// the wall-clock latency was spent blocked, mostly on I/O and locks.
+#[derive(Debug, Clone, Copy)]
+pub struct CpuTime {
+    pub p50_ms: f64,
+    pub p99_ms: f64,
+    pub cpu_fraction: f64, // total CPU time over total latency
+}
+
+// The dedicated reader/writer phase: writer threads only write and reader
+// threads only read, concurrently, each thread with its own histogram
+#[derive(Debug, Clone)]
+pub struct ReaderWriterRun {
+    pub writers: Vec<ThreadStats>,
+    pub readers: Vec<ThreadStats>,
+    pub write_throughput: f64, // all writers together
+    pub read_throughput: f64,
+    pub write_p99_ms: f64, // across all writers
+    pub read_p99_ms: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ThreadStats {
+    pub operations: u64,
+    pub throughput: f64,
+    pub p50_ms: f64,
+    pub p99_ms: f64,
+}
+
+// Write latency for values in one size range
+#[derive(Debug, Clone)]
+pub struct SizeBucketLatency {
+    pub label: &'static str,
+    pub writes: u64,
+    pub p99_ms: f64,
+}
+
+// Upper bounds (exclusive) of the value-size buckets write latency is split into
+const VALUE_SIZE_BUCKETS: [(&str, usize); 3] = [
+    ("<1KB", 1024),
+    ("1-16KB", 16 * 1024),
+    (">=16KB", usize::MAX),
+];
+
+// Latency across all operation types within one slice of the run
+#[derive(Debug, Clone)]
+pub struct LatencyWindow {
+    pub start_secs: f64,
+    pub operations: u64,
+    pub keys: u64, // in the database at the end of the window
+    pub p50_ms: f64,
+    pub p99_ms: f64,
+}
+
+// One burst of a Burst load pattern
+#[derive(Debug, Clone)]
+pub struct BurstStats {
+    pub start_secs: f64,
+    pub operations: u64,
+    pub p50_ms: f64,
+    pub p99_ms: f64,
+    pub compaction_pending_after_idle: Option<bool>, // None if the engine can't tell or the run ended first
+}
+
+// One phase of a phased run, across all its operation types
+#[derive(Debug, Clone)]
+pub struct PhaseStats {
+    pub name: &'static str,
+    pub operations: u64,
+    pub elapsed_secs: f64,
+    pub throughput: f64,
+    pub p99_ms: f64,
+}
+
+// How P99 latency moved with the number of keys across latency windows:
+// the Pearson correlation and the least-squares slope
+#[derive(Debug, Clone, Copy)]
+pub struct LatencyScaling {
+    pub correlation: f64,
+    pub p99_ms_per_million_keys: f64,
+}
+
+impl LatencyScaling {
+    // Needs at least three windows with operations and some change in key count
+    fn from_windows(windows: &[LatencyWindow]) -> Option<Self> {
+        let points: Vec<(f64, f64)> = windows.iter()
+            .filter(|w| w.operations > 0)
+            .map(|w| (w.keys as f64, w.p99_ms))
+            .collect();
+        if points.len() < 3 {
+            return None;
+        }
+        let n = points.len() as f64;
+        let (mean_x, mean_y) = (points.iter().map(|p| p.0).sum::<f64>() / n, points.iter().map(|p| p.1).sum::<f64>() / n);
+        let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+        let var_x: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+        let var_y: f64 = points.iter().map(|(_, y)| (y - mean_y).powi(2)).sum();
+        if var_x == 0.0 {
+            return None;
+        }
+        Some(Self {
+            correlation: if var_y == 0.0 { 0.0 } else { covariance / (var_x * var_y).sqrt() },
+            p99_ms_per_million_keys: covariance / var_x * 1_000_000.0,
+        })
+    }
+}
+
+// The bulk insert of prepopulate_keys that precedes the measured phase,
+// reported separately as in YCSB's load / run split. Includes the closing flush.
+#[derive(Debug, Clone, Copy)]
+pub struct LoadPhase {
+    pub keys: u64,
+    pub elapsed_secs: f64,
+    pub throughput: f64,
+    pub write_p99_ms: Option<f64>, // None for a bulk load, which has no per-key writes
+    pub bulk: bool,
+}
+
+// Write stalls seen by the background sampler. Sampling every
+// SAMPLE_INTERVAL, so stalls shorter than that can be missed and the
+// duration is an estimate.
+#[derive(Debug, Clone, Copy)]
+pub struct WriteStalls {
+    pub count: u64,
+    pub estimated_secs: f64,
+}
+
+// Rows range scans returned versus rows that passed scan_filter
+#[derive(Debug, Clone, Copy)]
+pub struct ScanSelectivity {
+    pub scanned: u64,
+    pub matched: u64,
+    pub matched_per_sec: f64, // effective scan throughput in useful rows
+}
+
+// A full compaction forced between the load and the measured phase, with
+// the read latency of the same probe reads before and after it
+#[derive(Debug, Clone, Copy)]
+pub struct FullCompaction {
+    pub elapsed_secs: f64,
+    pub read_p99_before_ms: f64,
+    pub read_p99_after_ms: f64,
+}
+
+// One iteration over the whole database, analytics-style
+#[derive(Debug, Clone, Copy)]
+pub struct FullScanStats {
+    pub keys: u64,
+    pub bytes: u64, // keys plus values
+    pub elapsed_secs: f64,
+    pub keys_per_sec: f64,
+    pub mb_per_sec: f64,
+}
+
+// Reads issued right after writing a new version of the same key
+#[derive(Debug, Clone, Copy)]
+pub struct StalenessCheck {
+    pub reads: u64,
+    pub stale_reads: u64, // saw an older version than the one just written
+    pub max_lag: u64, // most versions a read fell behind
+}
+
+// Checkpoints taken during the measured phase and the throughput the
+// workload kept up while one was in progress
+#[derive(Debug, Clone, Copy)]
+pub struct CheckpointStats {
+    pub count: u64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    pub throughput_during: f64, // ops/s while a checkpoint was running
+    pub throughput_between: f64, // ops/s the rest of the time
+}
+
+// Keys written with a TTL, checked once it has passed and expired keys were purged
+#[derive(Debug, Clone, Copy)]
+pub struct TtlCheck {
+    pub keys: u64,
+    pub readable_after_expiry: u64, // should be 0
+    pub size_before_bytes: Option<u64>, // data directory, before expiry
+    pub size_after_bytes: Option<u64>, // and after the purge
+}
+
+// Keys missing after a simulated crash: a copy of the data directory taken
+// while the engine still had it open, then reopened
+#[derive(Debug, Clone, Copy)]
+pub struct DurabilityCheck {
+    pub keys: u64, // written in each phase
+    pub lost_after_flush: u64,
+    pub lost_without_flush: u64, // written after the last explicit flush
+    // Written without a flush, copied after waiting out the engine's
+    // background flush interval; None for engines without one
+    pub lost_after_flush_window: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct RunSummary {
+    pub runs: u32,
+    pub throughput: Stats,
+    pub throughput_samples: Vec<f64>, // one per run, for significance tests
+    pub write_p99_ms: Stats,
+    pub read_p99_ms: Stats,
+    pub scan_p99_ms: Stats,
+}
+
+// Result of a two-sided Mann-Whitney U test between two sets of samples
+#[derive(Debug, Clone, Copy)]
+pub struct MannWhitney {
+    pub u: f64, // pairs where the first sample is larger, ties counting half
+    pub p_value: f64,
+}
+
+// Exact when there are no ties and the samples are small, otherwise the
+// normal approximation with tie and continuity corrections
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> MannWhitney {
+    let (n, m) = (a.len(), b.len());
+    let u: f64 = a.iter()
+        .flat_map(|x| b.iter().map(move |y| if x > y { 1.0 } else if x == y { 0.5 } else { 0.0 }))
+        .sum();
+    
+    let mut all: Vec<f64> = a.iter().chain(b).cloned().collect();
+    all.sort_by(f64::total_cmp);
+    let tie_groups: Vec<usize> = all.chunk_by(|x, y| x == y).map(<[f64]>::len).collect();
+    let has_ties = tie_groups.iter().any(|&t| t > 1);
+    
+    if n == 0 || m == 0 {
+        return MannWhitney { u, p_value: 1.0 };
+    }
+    let p_value = if !has_ties && n * m <= 400 {
+        // counts[i][j][k]: orderings of i a-samples and j b-samples with U = k
+        let mut counts = vec![vec![Vec::new(); m + 1]; n + 1];
+        for i in 0..=n {
+            for j in 0..=m {
+                counts[i][j] = if i == 0 || j == 0 {
+                    vec![1.0]
+                } else {
+                    // The largest sample is either an a (beating all j b's) or a b
+                    (0..=i * j).map(|k| {
+                        let from_a = if k >= j { counts[i - 1][j].get(k - j).copied().unwrap_or(0.0) } else { 0.0 };
+                        from_a + counts[i][j - 1].get(k).copied().unwrap_or(0.0)
+                    }).collect()
+                };
+            }
+        }
+        let dist = &counts[n][m];
+        let total: f64 = dist.iter().sum();
+        let k = u as usize;
+        let lower: f64 = dist[..=k].iter().sum::<f64>() / total;
+        let upper: f64 = dist[k..].iter().sum::<f64>() / total;
+        (2.0 * lower.min(upper)).min(1.0)
+    } else {
+        let (n, m) = (n as f64, m as f64);
+        let total = n + m;
+        let ties: f64 = tie_groups.iter().map(|&t| (t as f64).powi(3) - t as f64).sum();
+        let variance = n * m / 12.0 * ((total + 1.0) - ties / (total * (total - 1.0)));
+        if variance <= 0.0 {
+            1.0
+        } else {
+            let z = ((u - n * m / 2.0).abs() - 0.5).max(0.0) / variance.sqrt();
+            (2.0 * (1.0 - normal_cdf(z))).min(1.0)
+        }
+    };
+    MannWhitney { u, p_value }
+}
+
+// Abramowitz and Stegun 7.1.26, accurate to about 1e-7
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+    if z >= 0.0 { 0.5 * (1.0 + erf) } else { 0.5 * (1.0 - erf) }
+}
+
+pub fn encode_histogram(hist: &Histogram<u64>) -> Result<String, Box<dyn std::error::Error>> {
+    let mut buf = Vec::new();
+    V2DeflateSerializer::new().serialize(hist, &mut buf)?;
+    Ok(BASE64.encode(buf))
+}
+
+pub fn decode_histogram(encoded: &str) -> Result<Histogram<u64>, Box<dyn std::error::Error>> {
+    let buf = BASE64.decode(encoded)?;
+    Ok(Deserializer::new().deserialize(&mut buf.as_slice())?)
+}
+
+// Optional thresholds a run must meet; unset fields are not checked
+#[derive(Debug, Clone, Default)]
+pub struct Sla {
+    pub max_write_p99_ms: Option<f64>,
+    pub max_read_p99_ms: Option<f64>,
+    pub min_throughput: Option<f64>,
+}
+
+impl Sla {
+    pub fn violations(&self, result: &BenchmarkResult) -> Vec<String> {
+        let mut violations = Vec::new();
+        if let Some(max) = self.max_write_p99_ms.filter(|max| result.write_p99_ms > *max) {
+            violations.push(format!("{}: write P99 {:.2}ms exceeds {:.2}ms", result.engine_name, result.write_p99_ms, max));
+        }
+        if let Some(max) = self.max_read_p99_ms.filter(|max| result.read_p99_ms > *max) {
+            violations.push(format!("{}: read P99 {:.2}ms exceeds {:.2}ms", result.engine_name, result.read_p99_ms, max));
+        }
+        if let Some(min) = self.min_throughput.filter(|min| result.throughput < *min) {
+            violations.push(format!("{}: throughput {:.0} ops/s below {:.0} ops/s", result.engine_name, result.throughput, min));
+        }
+        violations
+    }
+}
+
+pub struct Benchmark {
+    pub seed: u64,
+    pub engine_config: EngineConfig,
+    pub write_ratio: u32,
+    pub scan_ratio: u32,
+    pub merge_ratio: u32,
+    pub prefix_scan_ratio: u32,
+    pub txn_ratio: u32,
+    pub txn_size: usize, // operations per transaction
+    pub multi_get_ratio: u32,
+    pub multi_get_size: usize, // keys per multi-get
+    pub value_size: usize,
+    // Size of values written during the measured phase when it differs from
+    // the prepopulated value_size, e.g. small values overwritten by large ones
+    pub write_value_size: Option<usize>,
+    // Derive each value from this seed and its key instead of the shared RNG,
+    // so a key's content doesn't depend on the operation mix. Keeps
+    // compression and space-amp numbers comparable across workloads.
+    pub value_seed: Option<u64>,
+    pub value_pool: Option<usize>, // draw write values from this many pre-generated buffers, keeping allocation out of write latency
+    pub compressibility: f64, // fraction of each value that is a repeated byte (0.0 = all random)
+    pub key_space: u64,
+    // Real keys and values to use instead of synthetic ones: key number n is
+    // the corpus's n-th key, and values are drawn from its values. Overrides
+    // key_encoding, key_size, value_size and compressibility.
+    pub corpus: Option<Corpus>,
+    pub key_encoding: KeyEncoding,
+    pub key_size: KeySizeDistribution,
+    // Benchmarks run side by side against one engine each own a disjoint
+    // slice of the keys: shard i of n uses [i * key_space, (i + 1) * key_space)
+    // of the n * key_space total. key_space and prepopulate_keys are per shard.
+    pub shard_index: u64,
+    pub shard_count: u64,
+    pub prepopulate_keys: u64, // keys written before the measured phase, at most key_space
+    pub skip_load: bool, // the engine already holds the prepopulated keys, e.g. from an earlier load
+    pub bulk_load: bool, // prepopulate through the engine's bulk path instead of one put per key
+    pub compact_before_run: bool, // fully compact after loading, for steady-state read numbers
+    // Before the run, read a sample of prepopulated keys and fail unless at
+    // least this fraction is found, catching engines that drop writes
+    pub verify_load: Option<f64>,
+    pub expected_fingerprint: Option<u64>, // fail the run unless the prepopulated data has this fingerprint
+    pub read_miss_ratio: f64, // fraction of reads aimed at keys that never exist
+    pub update_ratio: Option<f64>, // fraction of writes that overwrite a populated key; the rest insert new keys
+    pub num_operations: u64,
+    pub operation_mode: OperationMode,
+    // Explicit flush every this many operations; 0 leaves flushing to the engine.
+    // Frequent flushes cost throughput but bound how much an unclean exit loses.
+    pub flush_interval_ops: u64,
+    pub max_duration: Option<Duration>, // stop early if this elapses first
+    pub rate_limit: Option<f64>, // target operations per second; None runs flat out
+    pub load_pattern: LoadPattern,
+    pub convergence: Option<Convergence>,
+    pub checkpoint_interval: Option<Duration>, // take a checkpoint this often during the measured phase
+    pub min_free_disk: Option<u64>, // stop early once the data directory's disk has fewer free bytes
+    pub max_dataset_bytes: Option<u64>, // stop early once the data directory itself holds more bytes than this
+    pub scan_length: usize,
+    // Keep only scanned values containing this byte pattern, as a query would
+    // discard non-matching rows client-side. Scan latency includes the filtering.
+    pub scan_filter: Option<Vec<u8>>,
+    pub consistency_check: bool,
+    // After the run, this many threads write while reader_threads threads
+    // read, splitting num_operations between them, to show read/write
+    // interference that a single mixed loop averages away
+    pub writer_threads: usize,
+    pub reader_threads: usize,
+    pub staleness_check: bool, // read each write straight back and count older versions seen
+    pub full_scan: bool, // after the run, time one pass over every key
+    pub parse_engine_log: bool, // after the run, summarize the engine's own log (RocksDB's LOG)
+    pub self_check: bool, // verify basic put/get/delete/scan behaviour before the workload
+    pub repetitions: u32,
+    pub significance_alpha: f64, // p-value below which repeated runs' throughputs count as different
+    pub slow_op_threshold: Option<Duration>, // log operations slower than this to stderr
+    pub measure_cpu_time: bool, // also record each operation's thread CPU time
+    pub latency_sigfigs: u8, // histogram precision, 0 to 5 significant figures
+    pub max_latency: Duration, // highest latency the histograms track, unless they resize
+    pub auto_resize_histograms: bool, // grow histograms to fit slower operations instead of clamping them to max_latency
+    pub latency_window: Option<Duration>, // also keep one histogram per window of this length
+    pub stream_output: Option<PathBuf>, // append per-second JSON lines here while the run is in progress
+    pub measure_recovery: bool,
+    pub check_durability: bool, // after the run, verify written keys survive a simulated crash
+    pub sla: Sla,
+    pub compare_output: Option<PathBuf>, // also write compare_engines' table here as JSON
+    pub baseline: Option<String>, // registry name of the engine other engines are reported relative to
+    pub trace: Trace,
+}
+
+// Where the operation sequence comes from: the seeded generator, optionally
+// recorded to a trace file, or a previously recorded trace
+#[derive(Debug, Clone, Default)]
+pub enum Trace {
+    #[default]
+    Off,
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+enum WorkloadOp<'a> {
+    Write { key: Vec<u8>, value: Cow<'a, [u8]>, kind: WriteKind },
+    Scan { start: Vec<u8> },
+    Merge { key: Vec<u8> },
+    PrefixScan { prefix: Vec<u8> },
+    Txn(Vec<Op>),
+    Read { key: Vec<u8> },
+    MultiGet(Vec<Vec<u8>>),
+}
+
+// Whether a write is known to create or overwrite its key. Writes are
+// Unclassified unless update_ratio is set.
+#[derive(Clone, Copy)]
+enum WriteKind {
+    Unclassified,
+    Insert,
+    Update,
+}
+
+const TRACE_WRITE: u8 = 0;
+const TRACE_SCAN: u8 = 1;
+const TRACE_MERGE: u8 = 2;
+const TRACE_PREFIX_SCAN: u8 = 3;
+const TRACE_TXN: u8 = 4; // value_len holds the number of TRACE_TXN_* records that follow
+const TRACE_TXN_PUT: u8 = 5;
+const TRACE_TXN_DELETE: u8 = 6;
+const TRACE_READ: u8 = 7;
+const TRACE_INSERT: u8 = 8;
+const TRACE_UPDATE: u8 = 9;
+const TRACE_MULTI_GET: u8 = 10; // value_len holds the number of TRACE_MULTI_GET_KEY records that follow
+const TRACE_MULTI_GET_KEY: u8 = 11;
+
+fn write_traced_op(trace: &mut TraceWriter, op: &WorkloadOp) -> std::io::Result<()> {
+    let record = |kind, key: &[u8], value_len: usize| TraceRecord { kind, key: key.to_vec(), value_len: value_len as u32 };
+    match op {
+        WorkloadOp::Write { key, value, kind } => {
+            let kind = match kind {
+                WriteKind::Unclassified => TRACE_WRITE,
+                WriteKind::Insert => TRACE_INSERT,
+                WriteKind::Update => TRACE_UPDATE,
+            };
+            trace.write(&record(kind, key, value.len()))
+        }
+        WorkloadOp::Scan { start } => trace.write(&record(TRACE_SCAN, start, 0)),
+        WorkloadOp::Merge { key } => trace.write(&record(TRACE_MERGE, key, 8)),
+        WorkloadOp::PrefixScan { prefix } => trace.write(&record(TRACE_PREFIX_SCAN, prefix, 0)),
+        WorkloadOp::Txn(ops) => {
+            trace.write(&record(TRACE_TXN, &[], ops.len()))?;
+            for op in ops {
+                match op {
+                    Op::Put { key, value } => trace.write(&record(TRACE_TXN_PUT, key, value.len()))?,
+                    Op::Delete { key } => trace.write(&record(TRACE_TXN_DELETE, key, 0))?,
+                }
+            }
+            Ok(())
+        }
+        WorkloadOp::Read { key } => trace.write(&record(TRACE_READ, key, 0)),
+        WorkloadOp::MultiGet(keys) => {
+            trace.write(&record(TRACE_MULTI_GET, &[], keys.len()))?;
+            for key in keys {
+                trace.write(&record(TRACE_MULTI_GET_KEY, key, 0))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Rebuilds the next operation from a trace; values are regenerated at the recorded length
+fn read_traced_op(
+    trace: &mut TraceReader,
+    value: &mut dyn FnMut(usize) -> Vec<u8>,
+) -> Result<Option<WorkloadOp<'static>>, Box<dyn std::error::Error>> {
+    let Some(record) = trace.next().transpose()? else {
+        return Ok(None);
+    };
+    let op = match record.kind {
+        TRACE_WRITE | TRACE_INSERT | TRACE_UPDATE => {
+            let kind = match record.kind {
+                TRACE_INSERT => WriteKind::Insert,
+                TRACE_UPDATE => WriteKind::Update,
+                _ => WriteKind::Unclassified,
+            };
+            WorkloadOp::Write { value: Cow::Owned(value(record.value_len as usize)), key: record.key, kind }
+        }
+        TRACE_SCAN => WorkloadOp::Scan { start: record.key },
+        TRACE_MERGE => WorkloadOp::Merge { key: record.key },
+        TRACE_PREFIX_SCAN => WorkloadOp::PrefixScan { prefix: record.key },
+        TRACE_TXN => {
+            let mut ops = Vec::with_capacity(record.value_len as usize);
+            for _ in 0..record.value_len {
+                let sub = trace.next().ok_or("trace ends inside a transaction")??;
+                ops.push(match sub.kind {
+                    TRACE_TXN_PUT => Op::Put { value: value(sub.value_len as usize), key: sub.key },
+                    TRACE_TXN_DELETE => Op::Delete { key: sub.key },
+                    kind => return Err(format!("unexpected trace record {} inside a transaction", kind).into()),
+                });
+            }
+            WorkloadOp::Txn(ops)
+        }
+        TRACE_READ => WorkloadOp::Read { key: record.key },
+        TRACE_MULTI_GET => {
+            let mut keys = Vec::with_capacity(record.value_len as usize);
+            for _ in 0..record.value_len {
+                let sub = trace.next().ok_or("trace ends inside a multi-get")??;
+                if sub.kind != TRACE_MULTI_GET_KEY {
+                    return Err(format!("unexpected trace record {} inside a multi-get", sub.kind).into());
+                }
+                keys.push(sub.key);
+            }
+            WorkloadOp::MultiGet(keys)
+        }
+        kind => return Err(format!("unknown trace record kind {}", kind).into()),
+    };
+    Ok(Some(op))
+}
+
+impl Default for Benchmark {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct SlowOp {
+    op: &'static str,
+    key: Vec<u8>,
+    latency: Duration,
+}
+
+impl Benchmark {
+    pub fn new() -> Self {
+        Self {
+            seed: 42,
+            engine_config: EngineConfig::default(),
+            write_ratio: 70,
+            scan_ratio: 10,
+            merge_ratio: 0,
+            prefix_scan_ratio: 0,
+            txn_ratio: 0,
+            txn_size: 10,
+            multi_get_ratio: 0,
+            multi_get_size: 10,
+            value_size: 1024,
+            write_value_size: None,
+            value_seed: None,
+            value_pool: None,
+            compressibility: 1.0,
+            key_space: 10_000,
+            corpus: None,
+            key_encoding: KeyEncoding::String,
+            key_size: KeySizeDistribution::Fixed,
+            shard_index: 0,
+            shard_count: 1,
+            prepopulate_keys: 5_000,
+            skip_load: false,
+            bulk_load: false,
+            compact_before_run: false,
+            verify_load: None,
+            expected_fingerprint: None,
+            read_miss_ratio: 0.0,
+            update_ratio: None,
+            num_operations: 50_000,
+            operation_mode: OperationMode::Mixed,
+            flush_interval_ops: 5_000,
+            max_duration: None,
+            rate_limit: None,
+            load_pattern: LoadPattern::Steady,
+            convergence: None,
+            checkpoint_interval: None,
+            min_free_disk: None,
+            max_dataset_bytes: None,
+            scan_length: 100,
+            scan_filter: None,
+            consistency_check: false,
+            writer_threads: 0,
+            reader_threads: 0,
+            staleness_check: false,
+            full_scan: false,
+            parse_engine_log: false,
+            self_check: false,
+            repetitions: 1,
+            significance_alpha: 0.05,
+            slow_op_threshold: None,
+            measure_cpu_time: false,
+            latency_sigfigs: 3,
+            max_latency: Duration::from_secs(1),
+            auto_resize_histograms: false,
+            latency_window: None,
+            stream_output: None,
+            measure_recovery: false,
+            check_durability: false,
+            sla: Sla::default(),
+            compare_output: None,
+            baseline: None,
+            trace: Trace::Off,
+        }
+    }
+    
+    // Reads take whatever share the other operation ratios leave, so those
+    // must not add up to more than 100
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.shard_index >= self.shard_count.max(1) {
+            return Err(format!("shard_index {} is out of range for {} shard(s)", self.shard_index, self.shard_count).into());
+        }
+        // Keys past key_space belong to the read-miss and insert ranges
+        if self.key_space == 0 {
+            return Err("key_space must hold at least one key".into());
+        }
+        if self.prepopulate_keys > self.key_space {
+            return Err(format!("prepopulate_keys {} is more than the key_space of {}", self.prepopulate_keys, self.key_space).into());
+        }
+        
+        let ratios = [
+            ("write", self.write_ratio),
+            ("scan", self.scan_ratio),
+            ("merge", self.merge_ratio),
+            ("prefix_scan", self.prefix_scan_ratio),
+            ("txn", self.txn_ratio),
+            ("multi_get", self.multi_get_ratio),
+        ];
+        let total: u32 = ratios.iter().map(|(_, ratio)| ratio).sum();
+        if total > 100 {
+            let parts: Vec<String> = ratios.iter()
+                .map(|(name, ratio)| format!("{}={}", name, ratio))
+                .collect();
+            return Err(format!("operation ratios sum to {}, {} over 100 (reads take whatever is left below 100): {}",
+                total, total - 100, parts.join(" + ")).into());
+        }
+        
+        if let Some(rate) = self.rate_limit.filter(|rate| !(rate.is_finite() && *rate >= MIN_RATE)) {
+            return Err(format!("rate_limit must be at least {} ops/s, got {}", MIN_RATE, rate).into());
+        }
+        if let Some(max) = self.key_size.max_len().filter(|&max| max > MAX_KEY_LEN) {
+            return Err(format!("key_size allows keys of {} bytes, more than the {} a trace can record", max, MAX_KEY_LEN).into());
+        }
+        if self.latency_window.is_some_and(|window| window.is_zero()) {
+            return Err("latency_window must be longer than zero".into());
+        }
+        if let LoadPattern::Burst { peak_rate, burst, .. } = self.load_pattern {
+            if !(peak_rate.is_finite() && peak_rate >= MIN_RATE) {
+                return Err(format!("burst peak_rate must be at least {} ops/s, got {}", MIN_RATE, peak_rate).into());
+            }
+            // Burst indices come from dividing by burst + idle
+            if burst.is_zero() {
+                return Err("burst length must be positive".into());
+            }
+        }
+        Ok(())
+    }
+    
+    pub fn run(&self, engine: Arc<dyn StorageEngine>) -> Result<BenchmarkResult, Box<dyn std::error::Error>> {
+        self.validate()?;
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let new_hist = || self.new_histogram();
+        let mut write_hist = new_hist()?;
+        let mut insert_hist = new_hist()?;
+        let mut update_hist = new_hist()?;
+        let mut read_hist = new_hist()?;
+        let mut read_hit_hist = new_hist()?;
+        let mut read_miss_hist = new_hist()?;
+        let mut scan_hist = new_hist()?;
+        let mut merge_hist = new_hist()?;
+        let mut prefix_hist = new_hist()?;
+        let mut txn_hist = new_hist()?;
+        let mut multi_get_hist = new_hist()?;
+        let mut window_hists: Vec<Histogram<u64>> = Vec::new();
+        // Keys in the database, to relate each window's latency to data size
+        let mut window_keys: Vec<u64> = Vec::new();
+        let mut known_keys: Option<HashSet<Vec<u8>>> = self.latency_window
+            .map(|_| (0..self.prepopulate_keys).map(|i| self.key(i)).collect());
+        let mut cpu_hist = new_hist()?;
+        let (mut cpu_total, mut cpu_wall_total) = (Duration::ZERO, Duration::ZERO);
+        let phased = self.operation_mode == OperationMode::Phased;
+        let mut phase_hists = [new_hist()?, new_hist()?]; // write phase, then the rest
+        let mut write_phase_end = None;
+        let mut size_bucket_hists = VALUE_SIZE_BUCKETS.iter().map(|_| new_hist()).collect::<Result<Vec<_>, _>>()?;
+        // "key_000012" covers 100 keys; matches the engine's extractor when one is set
+        let prefix_length = self.engine_config.prefix_length.unwrap_or(10);
+        
+        if self.self_check {
+            Self::self_check(engine.as_ref())?;
+        }
+        
+        // Populate initial data; reads, scans and merges target this range
+        // while writes cover the whole key space
+        let populated = self.prepopulate_keys.max(1);
+        // Fresh inserts start past both the key space and the range read misses draw from
+        let mut next_insert = self.key_space * 2;
+        let value_pool = self.build_value_pool();
+        // Fingerprint of the prepopulated pairs in key-number order: as written
+        // by the load, or as read back when it was skipped
+        let mut fingerprint = Fingerprint::new();
+        let load = if self.skip_load {
+            for i in 0..self.prepopulate_keys {
+                let key = self.key(i);
+                fingerprint.add(&key, engine.get(&key)?.as_deref());
+            }
+            None
+        } else {
+            let load_value = |i: u64| match (self.value_seed, &self.corpus) {
+                (Some(seed), _) => self.keyed_value(seed, i, self.value_size),
+                (None, Some(corpus)) => corpus.value(i as usize).to_vec(), // the key's own value
+                (None, None) => vec![0u8; self.value_size],
+            };
+            let mut load_hist = new_hist()?;
+            let load_start;
+            if self.bulk_load {
+                // Built up front so only the engine's ingest is timed
+                let pairs: KvPairs = (0..self.prepopulate_keys).map(|i| (self.key(i), load_value(i))).collect();
+                for (key, value) in &pairs {
+                    fingerprint.add(key, Some(value));
+                }
+                load_start = Instant::now();
+                engine.bulk_load(pairs)?;
+            } else {
+                load_start = Instant::now();
+                for i in 0..self.prepopulate_keys {
+                    let key = self.key(i);
+                    let value = load_value(i);
+                    let put_start = Instant::now();
+                    engine.put(&key, &value)?;
+                    record_latency(&mut load_hist, put_start.elapsed());
+                    fingerprint.add(&key, Some(&value));
+                }
+            }
+            engine.flush()?;
+            let load_elapsed = load_start.elapsed();
+            Some(LoadPhase {
+                keys: self.prepopulate_keys,
+                elapsed_secs: load_elapsed.as_secs_f64(),
+                throughput: self.prepopulate_keys as f64 / load_elapsed.as_secs_f64(),
+                write_p99_ms: (!self.bulk_load).then(|| load_hist.value_at_percentile(99.0) as f64 / 1000.0),
+                bulk: self.bulk_load,
+            })
+        };
+        
+        let dataset_fingerprint = fingerprint.0;
+        if let Some(expected) = self.expected_fingerprint.filter(|&expected| expected != dataset_fingerprint) {
+            return Err(format!("dataset fingerprint {:016x} does not match the expected {:016x}",
+                dataset_fingerprint, expected).into());
+        }
+        
+        if let Some(min_hit_rate) = self.verify_load {
+            self.verify_prepopulated(engine.as_ref(), min_hit_rate)?;
+        }
+        
+        let full_compaction = if self.compact_before_run {
+            let read_p99_before_ms = self.probe_read_p99(engine.as_ref(), &mut new_hist()?)?;
+            let compact_start = Instant::now();
+            engine.compact()?;
+            let elapsed = compact_start.elapsed();
+            Some(FullCompaction {
+                elapsed_secs: elapsed.as_secs_f64(),
+                read_p99_before_ms,
+                read_p99_after_ms: self.probe_read_p99(engine.as_ref(), &mut new_hist()?)?,
+            })
+        } else {
+            None
+        };
+        
+        let disk_written_start = process_disk_bytes_written();
+        let sampler = Sampler::spawn(engine.clone(), self.min_free_disk, self.max_dataset_bytes);
+        let checkpointer = match self.checkpoint_interval {
+            Some(interval) => Some(Checkpointer::spawn(engine.clone(), interval)?),
+            None => None,
+        };
+        let mut ops_during_checkpoint = 0u64;
+        let mut flush_time = Duration::ZERO; // spent in the periodic flushes
+        let start = Instant::now();
+        let mut operations = 0u64;
+        let (mut writes, mut reads, mut scans, mut merges, mut prefix_scans) = (0u64, 0u64, 0u64, 0u64, 0u64);
+        let (mut txns, mut multi_gets) = (0u64, 0u64);
+        let (mut rows_scanned, mut rows_matched) = (0u64, 0u64);
+        let mut stop_reason = StopReason::OperationCount;
+        let mut converged_after = None;
+        let (mut window_start, mut window_ops, mut window_throughputs) = (start, 0u64, Vec::new());
+        
+        // Latencies past max_latency are clamped to it rather than failing the
+        // run, but counted so a too-small bound doesn't go unnoticed
+        let mut saturated_samples = 0u64;
+        let mut record = |hist: &mut Histogram<u64>, latency: Duration| {
+            if record_latency(hist, latency) {
+                saturated_samples += 1;
+            }
+        };
+        
+        let mut slow_ops_dropped = 0u64;
+        let (slow_log, slow_logger) = match self.slow_op_threshold {
+            Some(threshold) => {
+                let (tx, rx) = mpsc::sync_channel::<SlowOp>(1024);
+                let logger = thread::spawn(move || {
+                    for op in rx {
+                        eprintln!("slow {} key={} latency={:.3}ms",
+                            op.op, String::from_utf8_lossy(&op.key), op.latency.as_secs_f64() * 1000.0);
+                    }
+                });
+                (Some((threshold, tx)), Some(logger))
+            }
+            None => (None, None),
+        };
+        
+        let mut limiter = match self.load_pattern {
+            LoadPattern::Steady => self.rate_limit.map(RateLimiter::new),
+            LoadPattern::Burst { peak_rate, .. } => Some(RateLimiter::new(peak_rate)),
+        };
+        let mut burst_hists: Vec<Histogram<u64>> = Vec::new();
+        let mut pending_after_idle: Vec<Option<bool>> = Vec::new(); // per burst, sampled at the end of its idle period
+        let mut stream = match &self.stream_output {
+            Some(path) => Some(StreamOutput::open(path, engine.engine_name(), new_hist()?)?),
+            None => None,
+        };
+        let mut replay = match &self.trace {
+            Trace::Replay(path) => Some(TraceReader::open(path)?),
+            _ => None,
+        };
+        let mut recorder = match &self.trace {
+            Trace::Record(path) => Some(TraceWriter::create(path)?),
+            _ => None,
+        };
+        
+        loop {
+            let op = match replay.as_mut() {
+                Some(reader) => match read_traced_op(reader, &mut |len| self.generate_value_of_len(&mut rng, len))? {
+                    Some(op) => op,
+                    None => break,
+                },
+                None if operations >= self.num_operations => break,
+                None => self.next_op(&mut rng, operations, populated, prefix_length, &mut next_insert, &value_pool),
+            };
+            if self.max_duration.is_some_and(|d| start.elapsed() >= d) {
+                stop_reason = StopReason::Duration;
+                break;
+            }
+            if sampler.disk_low() {
+                stop_reason = StopReason::DiskSpace;
+                break;
+            }
+            if sampler.over_budget() {
+                stop_reason = StopReason::DatasetSize;
+                break;
+            }
+            if let Some(recorder) = recorder.as_mut() {
+                write_traced_op(recorder, &op)?;
+            }
+            if let LoadPattern::Burst { burst, idle, .. } = self.load_pattern {
+                let period = (burst + idle).as_secs_f64();
+                let elapsed = start.elapsed().as_secs_f64();
+                if elapsed % period >= burst.as_secs_f64() {
+                    thread::sleep(Duration::from_secs_f64(period - elapsed % period));
+                    let index = (elapsed / period) as usize;
+                    pending_after_idle.resize(pending_after_idle.len().max(index + 1), None);
+                    pending_after_idle[index] = engine.metrics().compaction_pending;
+                }
+            }
+            if let Some(limiter) = limiter.as_mut() {
+                limiter.acquire();
+            }
+
+            // Transactions update the key set once they commit, below
+            if let (Some(known), WorkloadOp::Write { key, .. }) = (known_keys.as_mut(), &op) {
+                known.insert(key.clone());
+            }
+
+            let cpu_start = if self.measure_cpu_time { thread_cpu_time() } else { None };
+            let op_start = Instant::now();
+            let (op_name, key, latency) = match op {
+                WorkloadOp::Write { key, value, kind } => {
+                    engine.put(&key, &value)?;
+                    let latency = op_start.elapsed();
+                    record(&mut write_hist, latency);
+                    let bucket = VALUE_SIZE_BUCKETS.iter().position(|(_, max)| value.len() < *max).unwrap_or(0);
+                    record(&mut size_bucket_hists[bucket], latency);
+                    match kind {
+                        WriteKind::Insert => record(&mut insert_hist, latency),
+                        WriteKind::Update => record(&mut update_hist, latency),
+                        WriteKind::Unclassified => {}
+                    }
+                    writes += 1;
+                    ("write", key, latency)
+                }
+                WorkloadOp::Scan { start } => {
+                    let rows = engine.range_scan(&start, self.scan_length)?;
+                    if let Some(pattern) = &self.scan_filter {
+                        rows_scanned += rows.len() as u64;
+                        rows_matched += rows.iter().filter(|(_, value)| contains(value, pattern)).count() as u64;
+                    }
+                    let latency = op_start.elapsed();
+                    record(&mut scan_hist, latency);
+                    scans += 1;
+                    ("scan", start, latency)
+                }
+                WorkloadOp::Merge { key } => {
+                    engine.merge(&key, &1u64.to_le_bytes())?;
+                    let latency = op_start.elapsed();
+                    record(&mut merge_hist, latency);
+                    merges += 1;
+                    ("merge", key, latency)
+                }
+                WorkloadOp::PrefixScan { prefix } => {
+                    let _ = engine.prefix_scan(&prefix)?;
+                    let latency = op_start.elapsed();
+                    record(&mut prefix_hist, latency);
+                    prefix_scans += 1;
+                    ("prefix_scan", prefix, latency)
+                }
+                WorkloadOp::Txn(ops) => {
+                    engine.txn(&ops)?;
+                    let latency = op_start.elapsed();
+                    record(&mut txn_hist, latency);
+                    txns += 1;
+                    if let Some(known) = known_keys.as_mut() {
+                        for op in &ops {
+                            match op {
+                                Op::Put { key, .. } => known.insert(key.clone()),
+                                Op::Delete { key } => known.remove(key),
+                            };
+                        }
+                    }
+                    let key = match ops.first() {
+                        Some(Op::Put { key, .. } | Op::Delete { key }) => key.clone(),
+                        None => Vec::new(),
+                    };
+                    ("txn", key, latency)
+                }
+                WorkloadOp::Read { key } => {
+                    let found = engine.get(&key)?.is_some();
+                    let latency = op_start.elapsed();
+                    record(&mut read_hist, latency);
+                    if found {
+                        record(&mut read_hit_hist, latency);
+                    } else {
+                        record(&mut read_miss_hist, latency);
+                    }
+                    reads += 1;
+                    ("read", key, latency)
+                }
+                WorkloadOp::MultiGet(keys) => {
+                    let _ = engine.multi_get(&keys)?;
+                    let latency = op_start.elapsed();
+                    record(&mut multi_get_hist, latency);
+                    multi_gets += 1;
+                    ("multi_get", keys.into_iter().next().unwrap_or_default(), latency)
+                }
+            };
+
+            if let Some(cpu) = cpu_start.zip(thread_cpu_time()).map(|(from, to)| to.saturating_sub(from)) {
+                record_latency(&mut cpu_hist, cpu);
+                cpu_total += cpu;
+                cpu_wall_total += latency;
+            }
+
+            if let Some(stream) = stream.as_mut() {
+                stream.record(latency)?;
+            }
+
+            if let Some(window) = self.latency_window {
+                let index = ((op_start - start).as_secs_f64() / window.as_secs_f64()) as usize;
+                let keys = known_keys.as_ref().map_or(0, |known| known.len() as u64);
+                while window_hists.len() <= index {
+                    window_hists.push(new_hist()?);
+                    window_keys.push(keys);
+                }
+                record_latency(&mut window_hists[index], latency);
+                window_keys[index] = keys;
+            }
+
+            if let LoadPattern::Burst { burst, idle, .. } = self.load_pattern {
+                let index = ((op_start - start).as_secs_f64() / (burst + idle).as_secs_f64()) as usize;
+                while burst_hists.len() <= index {
+                    burst_hists.push(new_hist()?);
+                }
+                record_latency(&mut burst_hists[index], latency);
+            }
+
+            if let Some((threshold, tx)) = &slow_log {
+                if latency > *threshold {
+                    let op = SlowOp { op: op_name, key, latency };
+                    // Never block the benchmark on the logger
+                    if tx.try_send(op).is_err() {
+                        slow_ops_dropped += 1;
+                    }
+                }
+            }
+
+            if phased {
+                let phase = usize::from(operations >= self.write_phase_ops());
+                record_latency(&mut phase_hists[phase], latency);
+            }
+            operations += 1;
+            if phased && operations == self.write_phase_ops() {
+                write_phase_end = Some(Instant::now());
+            }
+            if checkpointer.as_ref().is_some_and(Checkpointer::active) {
+                ops_during_checkpoint += 1;
+            }
+
+            if self.flush_interval_ops > 0 && operations.is_multiple_of(self.flush_interval_ops) {
+                let flush_start = Instant::now();
+                engine.flush()?;
+                flush_time += flush_start.elapsed();
+            }
+
+            if let Some(convergence) = &self.convergence {
+                window_ops += 1;
+                if window_start.elapsed() >= convergence.window {
+                    window_throughputs.push(window_ops as f64 / window_start.elapsed().as_secs_f64());
+                    (window_start, window_ops) = (Instant::now(), 0);
+                    if let Some(first) = window_throughputs.len().checked_sub(convergence.windows.max(2)) {
+                        let recent = Stats::from_samples(&window_throughputs[first..]);
+                        if recent.stddev / recent.mean < convergence.max_cv {
+                            stop_reason = StopReason::Converged;
+                            converged_after = Some(start.elapsed());
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        
+        engine.flush()?;
+        let disk_written_end = process_disk_bytes_written();
+        
+        let elapsed = start.elapsed();
+        let end = start + elapsed;
+        let samples = sampler.finish();
+        if let Some(mut stream) = stream {
+            stream.emit()?;
+        }
+        let checkpoints = match checkpointer {
+            Some(checkpointer) => {
+                let durations = checkpointer.finish()?;
+                let busy: f64 = durations.iter().map(Duration::as_secs_f64).sum();
+                Some(CheckpointStats {
+                    count: durations.len() as u64,
+                    mean_ms: if durations.is_empty() { 0.0 } else { busy * 1000.0 / durations.len() as f64 },
+                    max_ms: durations.iter().max().map_or(0.0, |d| d.as_secs_f64() * 1000.0),
+                    throughput_during: if busy > 0.0 { ops_during_checkpoint as f64 / busy } else { 0.0 },
+                    throughput_between: (operations - ops_during_checkpoint) as f64 / (elapsed.as_secs_f64() - busy).max(f64::EPSILON),
+                })
+            }
+            None => None,
+        };
+        if let Some(recorder) = recorder {
+            recorder.finish()?;
+        }
+        
+        drop(slow_log);
+        if let Some(logger) = slow_logger {
+            let _ = logger.join();
+        }
+        if slow_ops_dropped > 0 {
+            eprintln!("{} slow operations not logged (logger backlog full)", slow_ops_dropped);
+        }
+        
+        let throughput = operations as f64 / elapsed.as_secs_f64();
+        
+        // The checks below write more data, so they're skipped once a storage limit stopped the run
+        let may_write = !stop_reason.storage_limited();
+        let consistency_violations = if self.consistency_check && may_write {
+            Some(self.check_scan_consistency(engine.as_ref())?)
+        } else {
+            None
+        };
+        let reader_writer = if self.writer_threads + self.reader_threads > 0 && may_write {
+            Some(self.run_reader_writer(engine.as_ref(), populated)?)
+        } else {
+            None
+        };
+        let staleness = if self.staleness_check && may_write {
+            Some(self.check_staleness(engine.as_ref())?)
+        } else {
+            None
+        };
+        let engine_log = if self.parse_engine_log {
+            engine.log_summary()?
+        } else {
+            None
+        };
+        let full_scan = if self.full_scan {
+            let scan_start = Instant::now();
+            let (keys, bytes) = engine.full_scan()?;
+            let secs = scan_start.elapsed().as_secs_f64();
+            Some(FullScanStats {
+                keys,
+                bytes,
+                elapsed_secs: secs,
+                keys_per_sec: keys as f64 / secs,
+                mb_per_sec: bytes as f64 / 1024.0 / 1024.0 / secs,
+            })
+        } else {
+            None
+        };
+        
+        let latency_windows = self.latency_window.map_or(Vec::new(), |window| {
+            window_hists.iter().zip(&window_keys).enumerate().map(|(i, (hist, keys))| LatencyWindow {
+                start_secs: i as f64 * window.as_secs_f64(),
+                operations: hist.len(),
+                keys: *keys,
+                p50_ms: hist.value_at_percentile(50.0) as f64 / 1000.0,
+                p99_ms: hist.value_at_percentile(99.0) as f64 / 1000.0,
+            }).collect()
+        });
+        
+        // A run cut short in the write phase never reaches the second one
+        let write_phase_end = write_phase_end.unwrap_or(if phase_hists[1].is_empty() { end } else { start });
+        let phases = if phased {
+            [("write", start, write_phase_end), ("read", write_phase_end, end)].iter().zip(&phase_hists)
+                .filter(|(_, hist)| !hist.is_empty())
+                .map(|((name, from, to), hist)| {
+                    let secs = to.duration_since(*from).as_secs_f64();
+                    PhaseStats {
+                        name,
+                        operations: hist.len(),
+                        elapsed_secs: secs,
+                        throughput: hist.len() as f64 / secs,
+                        p99_ms: hist.value_at_percentile(99.0) as f64 / 1000.0,
+                    }
+                }).collect()
+        } else {
+            Vec::new()
+        };
+        
+        let metrics = engine.metrics();
+        // Runs after metrics are taken, as the purge also removes expired workload keys
+        let ttl = match self.engine_config.ttl {
+            Some(ttl) if may_write => Some(self.check_ttl(engine.as_ref(), ttl)?),
+            _ => None,
+        };
+        // Prefer what the OS saw during the measured phase; engine counters
+        // are the fallback, and also cover the populate phase
+        let disk_bytes_written = match (disk_written_start, disk_written_end) {
+            (Some(start), Some(end)) => Some(end.saturating_sub(start)),
+            _ => metrics.physical_bytes_written,
+        };
+        Ok(BenchmarkResult {
+            engine_name: engine.engine_name().to_string(),
+            engine_version: engine.engine_version(),
+            seed: self.seed,
+            operations,
+            throughput,
+            throughput_excluding_flush: operations as f64 / (elapsed - flush_time.min(elapsed)).as_secs_f64().max(f64::EPSILON),
+            flush_secs: flush_time.as_secs_f64(),
+            write_throughput: writes as f64 / elapsed.as_secs_f64(),
+            read_throughput: reads as f64 / elapsed.as_secs_f64(),
+            scan_throughput: scans as f64 / elapsed.as_secs_f64(),
+            merge_throughput: merges as f64 / elapsed.as_secs_f64(),
+            prefix_scan_throughput: prefix_scans as f64 / elapsed.as_secs_f64(),
+            txn_throughput: txns as f64 / elapsed.as_secs_f64(),
+            multi_get_throughput: multi_gets as f64 / elapsed.as_secs_f64(),
+            load,
+            dataset_fingerprint,
+            stop_reason,
+            converged_after_secs: converged_after.map(|d| d.as_secs_f64()),
+            write_p99_ms: write_hist.value_at_percentile(99.0) as f64 / 1000.0,
+            insert_p99_ms: insert_hist.value_at_percentile(99.0) as f64 / 1000.0,
+            update_p99_ms: update_hist.value_at_percentile(99.0) as f64 / 1000.0,
+            read_p99_ms: read_hist.value_at_percentile(99.0) as f64 / 1000.0,
+            read_hit_p99_ms: read_hit_hist.value_at_percentile(99.0) as f64 / 1000.0,
+            read_miss_p99_ms: read_miss_hist.value_at_percentile(99.0) as f64 / 1000.0,
+            scan_p99_ms: scan_hist.value_at_percentile(99.0) as f64 / 1000.0,
+            merge_p99_ms: merge_hist.value_at_percentile(99.0) as f64 / 1000.0,
+            prefix_scan_p99_ms: prefix_hist.value_at_percentile(99.0) as f64 / 1000.0,
+            txn_commit_p99_ms: txn_hist.value_at_percentile(99.0) as f64 / 1000.0,
+            multi_get_p99_ms: multi_get_hist.value_at_percentile(99.0) as f64 / 1000.0,
+            saturated_samples,
+            cpu_time: (!cpu_hist.is_empty()).then(|| CpuTime {
+                p50_ms: cpu_hist.value_at_percentile(50.0) as f64 / 1000.0,
+                p99_ms: cpu_hist.value_at_percentile(99.0) as f64 / 1000.0,
+                cpu_fraction: cpu_total.as_secs_f64() / cpu_wall_total.as_secs_f64(),
+            }),
+            write_histogram: encode_histogram(&write_hist)?,
+            read_histogram: encode_histogram(&read_hist)?,
+            scan_histogram: encode_histogram(&scan_hist)?,
+            latency_scaling: LatencyScaling::from_windows(&latency_windows),
+            latency_windows,
+            phases,
+            bursts: match self.load_pattern {
+                LoadPattern::Steady => Vec::new(),
+                LoadPattern::Burst { burst, idle, .. } => burst_hists.iter().enumerate().map(|(i, hist)| BurstStats {
+                    start_secs: i as f64 * (burst + idle).as_secs_f64(),
+                    operations: hist.len(),
+                    p50_ms: hist.value_at_percentile(50.0) as f64 / 1000.0,
+                    p99_ms: hist.value_at_percentile(99.0) as f64 / 1000.0,
+                    compaction_pending_after_idle: pending_after_idle.get(i).copied().flatten(),
+                }).collect(),
+            },
+            write_size_buckets: VALUE_SIZE_BUCKETS.iter().zip(&size_bucket_hists).map(|((label, _), hist)| SizeBucketLatency {
+                label,
+                writes: hist.len(),
+                p99_ms: hist.value_at_percentile(99.0) as f64 / 1000.0,
+            }).collect(),
+            scan_selectivity: self.scan_filter.as_ref().map(|_| ScanSelectivity {
+                scanned: rows_scanned,
+                matched: rows_matched,
+                matched_per_sec: rows_matched as f64 / elapsed.as_secs_f64(),
+            }),
+            consistency_violations,
+            reader_writer,
+            staleness,
+            full_scan,
+            full_compaction,
+            engine_log,
+            checkpoints,
+            ttl,
+            peak_space_amplification: samples.peak_space_amplification.max(metrics.space_amplification),
+            peak_open_fds: samples.peak_open_fds,
+            peak_threads: samples.peak_threads,
+            write_stalls: samples.stall_samples.map(|stalled| WriteStalls {
+                count: samples.stalls,
+                estimated_secs: stalled as f64 * SAMPLE_INTERVAL.as_secs_f64(),
+            }),
+            disk_bytes_written,
+            open_latency_ms: None,
+            recovery_ms: None,
+            durability: None,
+            summary: None,
+            metrics,
+        })
+    }
+    
+    // Draws the next operation according to the configured ratios
+    fn next_op<'a>(
+        &self,
+        rng: &mut StdRng,
+        index: u64,
+        populated: u64,
+        prefix_length: usize,
+        next_insert: &mut u64,
+        value_pool: &'a [Vec<u8>],
+    ) -> WorkloadOp<'a> {
+        let op_type = rng.gen_range(self.op_types(index));
+        
+        if op_type < self.write_ratio {
+            let (key_num, kind) = match self.update_ratio {
+                None => (rng.gen_range(0..self.key_space), WriteKind::Unclassified),
+                Some(ratio) if rng.gen_bool(ratio.clamp(0.0, 1.0)) => (rng.gen_range(0..populated), WriteKind::Update),
+                Some(_) => {
+                    *next_insert += 1;
+                    (*next_insert - 1, WriteKind::Insert)
+                }
+            };
+            let key = self.key(key_num);
+            let value = if let Some(seed) = self.value_seed {
+                Cow::Owned(self.keyed_value(seed, key_num, self.write_value_len()))
+            } else if value_pool.is_empty() {
+                Cow::Owned(self.generate_value(rng))
+            } else {
+                Cow::Borrowed(value_pool[rng.gen_range(0..value_pool.len())].as_slice())
+            };
+            WorkloadOp::Write { key, value, kind }
+        } else if op_type < self.write_ratio + self.scan_ratio {
+            let key_num = rng.gen_range(0..populated);
+            WorkloadOp::Scan { start: self.key(key_num) }
+        } else if op_type < self.write_ratio + self.scan_ratio + self.merge_ratio {
+            let key_num = rng.gen_range(0..populated);
+            WorkloadOp::Merge { key: self.counter_key(key_num) }
+        } else if op_type < self.write_ratio + self.scan_ratio + self.merge_ratio + self.prefix_scan_ratio {
+            let key_num = rng.gen_range(0..populated);
+            let mut prefix = self.key(key_num);
+            prefix.truncate(prefix_length);
+            WorkloadOp::PrefixScan { prefix }
+        } else if op_type < self.write_ratio + self.scan_ratio + self.merge_ratio + self.prefix_scan_ratio + self.txn_ratio {
+            // One in ten ops deletes so transactions also churn the key space
+            WorkloadOp::Txn((0..self.txn_size).map(|_| {
+                let key_num = rng.gen_range(0..self.key_space);
+                let key = self.key(key_num);
+                if rng.gen_range(0..10) == 0 {
+                    Op::Delete { key }
+                } else {
+                    let value = match self.value_seed {
+                        Some(seed) => self.keyed_value(seed, key_num, self.write_value_len()),
+                        None => self.generate_value(rng),
+                    };
+                    Op::Put { key, value }
+                }
+            }).collect())
+        } else if op_type < self.write_ratio + self.scan_ratio + self.merge_ratio + self.prefix_scan_ratio + self.txn_ratio + self.multi_get_ratio {
+            WorkloadOp::MultiGet((0..self.multi_get_size).map(|_| self.key(rng.gen_range(0..populated))).collect())
+        } else {
+            // Keys past the key space are never written, so these reads always miss
+            let key_num = if self.read_miss_ratio > 0.0 && rng.gen_bool(self.read_miss_ratio.clamp(0.0, 1.0)) {
+                self.key_space + rng.gen_range(0..self.key_space)
+            } else {
+                rng.gen_range(0..populated)
+            };
+            WorkloadOp::Read { key: self.key(key_num) }
+        }
+    }
+    
+    // Operations whose op_type falls in this range may be drawn as the
+    // index-th operation: ratios are stacked from writes up to reads at 100
+    fn op_types(&self, index: u64) -> std::ops::Range<u32> {
+        match self.operation_mode {
+            OperationMode::Mixed => 0..100,
+            OperationMode::Phased if index < self.write_phase_ops() => 0..self.write_ratio,
+            OperationMode::Phased => self.write_ratio.min(99)..100,
+        }
+    }
+    
+    fn write_phase_ops(&self) -> u64 {
+        self.num_operations * self.write_ratio.min(100) as u64 / 100
+    }
+    
+    fn key(&self, n: u64) -> Vec<u8> {
+        let n = self.sharded_key_number(n);
+        if let Some(corpus) = &self.corpus {
+            return corpus.key(n);
+        }
+        let mut key = match self.key_encoding {
+            KeyEncoding::String => format!("key_{:08}", n).into_bytes(),
+            KeyEncoding::U64Be => n.to_be_bytes().to_vec(),
+            KeyEncoding::Uuid => {
+                // The first 64 bits are mix64(n), a bijection, so distinct numbers never collide
+                let (hi, lo) = (mix64(n), mix64(!n));
+                format!("{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+                    hi >> 32, (hi >> 16) & 0xffff, hi & 0xffff,
+                    lo >> 48, lo & 0xffff_ffff_ffff).into_bytes()
+            }
+            KeyEncoding::Composite => {
+                let mut key = ((n % 64) as u16).to_be_bytes().to_vec();
+                key.extend_from_slice(&(n / 64).to_be_bytes());
+                key
+            }
+        };
+        // Padding after the encoded number keeps keys unique and in the same order
+        if let Some(len) = self.key_size.len_for(n) {
+            if len > key.len() {
+                key.resize(len, b'_');
+            }
+        }
+        key
+    }
+    
+    // Counters live apart from the values merges would otherwise clobber, but
+    // go through key() so sharding keeps each shard's counters separate
+    fn counter_key(&self, n: u64) -> Vec<u8> {
+        let mut key = b"counter_".to_vec();
+        key.extend_from_slice(&self.key(n));
+        key
+    }
+    
+    // Key numbers fall into key_space-wide regions: the keys proper, then the
+    // read-miss range, then fresh inserts. Sharding widens every region to
+    // shard_count * key_space and gives shard i the i-th slice of each, so
+    // shards' keys never collide.
+    fn sharded_key_number(&self, n: u64) -> u64 {
+        let (shards, span) = (self.shard_count.max(1), self.key_space.max(1));
+        (n / span) * span * shards + self.shard_index * span + n % span
+    }
+    
+    // Generated up front, from their own RNG, so no write pays for building its value
+    fn build_value_pool(&self) -> Vec<Vec<u8>> {
+        let mut pool_rng = StdRng::seed_from_u64(!self.seed);
+        (0..self.value_pool.unwrap_or(0)).map(|_| self.generate_value(&mut pool_rng)).collect()
+    }
+    
+    // Generates the seeded operation sequence twice and checks both traces are
+    // byte-identical, catching nondeterminism that creeps into the generator
+    pub fn verify_reproducible(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut traces = Vec::new();
+        for run in 0..2 {
+            let path = dir.path().join(format!("run{}.trace", run));
+            let mut trace = TraceWriter::create(&path)?;
+            let mut rng = StdRng::seed_from_u64(self.seed);
+            let mut next_insert = self.key_space * 2;
+            let value_pool = self.build_value_pool();
+            let prefix_length = self.engine_config.prefix_length.unwrap_or(10);
+            for index in 0..self.num_operations {
+                let op = self.next_op(&mut rng, index, self.prepopulate_keys.max(1), prefix_length, &mut next_insert, &value_pool);
+                write_traced_op(&mut trace, &op)?;
+            }
+            trace.finish()?;
+            traces.push(fs::read(&path)?);
+        }
+        if traces[0] != traces[1] {
+            return Err(format!("seed {} produced two different operation sequences", self.seed).into());
+        }
+        Ok(())
+    }
+    
+    // Always the same value for the same key number and length
+    fn keyed_value(&self, seed: u64, key_num: u64, len: usize) -> Vec<u8> {
+        self.generate_value_of_len(&mut StdRng::seed_from_u64(seed ^ mix64(key_num)), len)
+    }
+    
+    // Values written during the measured phase
+    fn generate_value(&self, rng: &mut StdRng) -> Vec<u8> {
+        self.generate_value_of_len(rng, self.write_value_len())
+    }
+    
+    fn write_value_len(&self) -> usize {
+        self.write_value_size.unwrap_or(self.value_size)
+    }
+    
+    // Random bytes followed by a run of one repeated byte, so values compress
+    // to roughly `1 - compressibility` of their size. With a corpus, a random
+    // corpus value whatever its length.
+    fn generate_value_of_len(&self, rng: &mut StdRng, len: usize) -> Vec<u8> {
+        if let Some(corpus) = &self.corpus {
+            return corpus.value(rng.gen_range(0..corpus.len())).to_vec();
+        }
+        let random_len = ((1.0 - self.compressibility.clamp(0.0, 1.0)) * len as f64) as usize;
+        let mut value = vec![0u8; random_len];
+        rng.fill(value.as_mut_slice());
+        value.resize(len, rng.gen::<u8>());
+        value
+    }
+    
+    // Runs the benchmark `repetitions` times, each against a fresh database,
+    // and returns the last run annotated with a summary across all runs.
+    pub fn run_repeated<F>(&self, open: F) -> Result<BenchmarkResult, Box<dyn std::error::Error>>
+    where
+        F: Fn(&Path, &EngineConfig) -> Result<Arc<dyn StorageEngine>, Box<dyn std::error::Error>>,
+    {
+        let mut runs = Vec::new();
+        for _ in 0..self.repetitions.max(1) {
+            let dir = tempfile::tempdir()?;
+            let open_start = Instant::now();
+            let engine = open(dir.path(), &self.engine_config)?;
+            let open_latency = open_start.elapsed();
+
+            // run() consumes the engine, so the database is closed once it returns
+            let mut result = self.run(engine)?;
+            result.open_latency_ms = Some(open_latency.as_secs_f64() * 1000.0);
 
-impl Benchmark {
-    pub fn new() -> Self {
-        Self {
-            write_ratio: 70,
-            scan_ratio: 10,
-            value_size: 1024,
-            num_operations: 50_000,
-            scan_length: 100,
+            if self.measure_recovery {
+                let reopen_start = Instant::now();
+                let engine = open(dir.path(), &self.engine_config)?;
+                result.recovery_ms = Some(reopen_start.elapsed().as_secs_f64() * 1000.0);
+                drop(engine);
+            }
+            if self.check_durability && !result.stop_reason.storage_limited() {
+                result.durability = Some(self.check_durability(&open, dir.path())?);
+            }
+            runs.push(result);
         }
+        
+        let stats = |f: fn(&BenchmarkResult) -> f64| {
+            Stats::from_samples(&runs.iter().map(f).collect::<Vec<_>>())
+        };
+        let summary = (runs.len() > 1).then(|| RunSummary {
+            runs: runs.len() as u32,
+            throughput: stats(|r| r.throughput),
+            throughput_samples: runs.iter().map(|r| r.throughput).collect(),
+            write_p99_ms: stats(|r| r.write_p99_ms),
+            read_p99_ms: stats(|r| r.read_p99_ms),
+            scan_p99_ms: stats(|r| r.scan_p99_ms),
+        });
+        
+        let mut result = runs.pop().unwrap();
+        result.summary = summary;
+        Ok(result)
     }
     
-    pub fn run(&self, engine: Arc<dyn StorageEngine>) -> Result<BenchmarkResult, Box<dyn std::error::Error>> {
-        let mut rng = StdRng::seed_from_u64(42);
-        let mut write_hist = Histogram::<u64>::new_with_bounds(1, 1_000_000, 3)?;
-        let mut read_hist = Histogram::<u64>::new_with_bounds(1, 1_000_000, 3)?;
-        let mut scan_hist = Histogram::<u64>::new_with_bounds(1, 1_000_000, 3)?;
+    // Cycles versions through a few keys, reading each straight back after
+    // writing it. A value that is missing or older than the version just
+    // written is stale; versions are little-endian u64s.
+    fn check_staleness(&self, engine: &dyn StorageEngine) -> Result<StalenessCheck, Box<dyn std::error::Error>> {
+        const KEYS: u64 = 16;
+        const READS: u64 = 10_000;
+        let mut check = StalenessCheck { reads: READS, stale_reads: 0, max_lag: 0 };
+        for version in 1..=READS {
+            let key = format!("staleness_{:04}", version % KEYS).into_bytes();
+            engine.put(&key, &version.to_le_bytes())?;
+            let seen = engine.get(&key)?
+                .and_then(|v| v.get(..8).map(|b| u64::from_le_bytes(b.try_into().unwrap())))
+                .unwrap_or(0);
+            if seen < version {
+                check.stale_reads += 1;
+                check.max_lag = check.max_lag.max((version - seen).div_ceil(KEYS));
+            }
+        }
+        Ok(check)
+    }
+    
+    fn verify_prepopulated(&self, engine: &dyn StorageEngine, min_hit_rate: f64) -> Result<(), Box<dyn std::error::Error>> {
+        const SAMPLE: u64 = 1_000;
+        if self.prepopulate_keys == 0 {
+            return Ok(());
+        }
+        // Its own RNG, so verifying doesn't change the workload
+        let mut rng = StdRng::seed_from_u64(mix64(self.seed));
+        let sampled = self.prepopulate_keys.min(SAMPLE);
+        let mut found = 0u64;
+        for _ in 0..sampled {
+            if engine.get(&self.key(rng.gen_range(0..self.prepopulate_keys)))?.is_some() {
+                found += 1;
+            }
+        }
+        let hit_rate = found as f64 / sampled as f64;
+        if hit_rate < min_hit_rate {
+            return Err(format!("only {} of {} sampled prepopulated keys are readable ({:.1}%, need {:.1}%)",
+                found, sampled, hit_rate * 100.0, min_hit_rate * 100.0).into());
+        }
+        Ok(())
+    }
+    
+    // Reads up to PROBE_READS prepopulated keys spread evenly over the
+    // populated range, returning their P99 in milliseconds
+    fn probe_read_p99(&self, engine: &dyn StorageEngine, hist: &mut Histogram<u64>) -> Result<f64, Box<dyn std::error::Error>> {
+        const PROBE_READS: u64 = 10_000;
+        let reads = self.prepopulate_keys.min(PROBE_READS);
+        for i in 0..reads {
+            let key = self.key(i * self.prepopulate_keys / reads);
+            let read_start = Instant::now();
+            engine.get(&key)?;
+            record_latency(hist, read_start.elapsed());
+        }
+        Ok(hist.value_at_percentile(99.0) as f64 / 1000.0)
+    }
+    
+    // Writes keys with a TTL, waits it out, purges, and counts what is still
+    // readable. Values are random so the reclaimed space isn't compressed away.
+    fn check_ttl(&self, engine: &dyn StorageEngine, ttl: Duration) -> Result<TtlCheck, Box<dyn std::error::Error>> {
+        const KEYS: u64 = 1_000;
+        let key = |i: u64| format!("ttl_{:08}", i).into_bytes();
+        let dir_size = || engine.data_dir().and_then(|dir| fs_size(dir).ok());
         
-        // Populate initial data
-        for i in 0..5000 {
-            let key = format!("key_{:08}", i).into_bytes();
-            let value = vec![0u8; self.value_size];
-            engine.put(&key, &value)?;
+        let mut value_rng = StdRng::seed_from_u64(self.seed);
+        for i in 0..KEYS {
+            let mut value = vec![0u8; self.value_size];
+            value_rng.fill(value.as_mut_slice());
+            engine.put_with_ttl(&key(i), &value, ttl)?;
         }
         engine.flush()?;
+        let size_before_bytes = dir_size();
         
-        let start = Instant::now();
-        let mut operations = 0u64;
+        // RocksDB tracks TTLs in whole seconds
+        thread::sleep(ttl + Duration::from_secs(1));
+        engine.purge_expired()?;
+        engine.flush()?;
         
-        for _ in 0..self.num_operations {
-            let op_start = Instant::now();
-            let op_type = rng.gen_range(0..100);
-            
-            if op_type < self.write_ratio {
-                let key_num = rng.gen_range(0..10000);
-                let key = format!("key_{:08}", key_num).into_bytes();
-                let value = vec![rng.gen::<u8>(); self.value_size];
-                engine.put(&key, &value)?;
-                write_hist.record(op_start.elapsed().as_micros() as u64)?;
-            } else if op_type < self.write_ratio + self.scan_ratio {
-                let key_num = rng.gen_range(0..5000);
-                let key = format!("key_{:08}", key_num).into_bytes();
-                let _ = engine.range_scan(&key, self.scan_length)?;
-                scan_hist.record(op_start.elapsed().as_micros() as u64)?;
-            } else {
-                let key_num = rng.gen_range(0..5000);
-                let key = format!("key_{:08}", key_num).into_bytes();
-                let _ = engine.get(&key)?;
-                read_hist.record(op_start.elapsed().as_micros() as u64)?;
-            }
-            
-            operations += 1;
-            
-            if operations % 5_000 == 0 {
-                engine.flush()?;
+        let mut readable_after_expiry = 0;
+        for i in 0..KEYS {
+            if engine.get(&key(i))?.is_some() {
+                readable_after_expiry += 1;
             }
         }
+        Ok(TtlCheck { keys: KEYS, readable_after_expiry, size_before_bytes, size_after_bytes: dir_size() })
+    }
+    
+    // Writes one batch of keys and flushes, writes a second without flushing,
+    // then copies the data directory while the engine still has it open, as a
+    // crash would leave it, and counts what the reopened copy can't read back.
+    // Engines that flush in the background get a third batch, copied only
+    // after their flush interval has passed.
+    fn check_durability<F>(&self, open: &F, path: &Path) -> Result<DurabilityCheck, Box<dyn std::error::Error>>
+    where
+        F: Fn(&Path, &EngineConfig) -> Result<Arc<dyn StorageEngine>, Box<dyn std::error::Error>>,
+    {
+        const KEYS: u64 = 1_000;
+        let key = |batch: &str, i: u64| format!("durable_{}_{:08}", batch, i).into_bytes();
+        let value = |i: u64| i.to_le_bytes().to_vec();
+        let crashes = tempfile::tempdir()?;
+        let crash = |name: &str| -> Result<Arc<dyn StorageEngine>, Box<dyn std::error::Error>> {
+            let copy = crashes.path().join(name);
+            copy_dir(path, &copy)?;
+            open(&copy, &self.engine_config)
+        };
+        let lost = |engine: &dyn StorageEngine, batch: &str| -> Result<u64, Box<dyn std::error::Error>> {
+            let mut lost = 0;
+            for i in 0..KEYS {
+                if engine.get(&key(batch, i))? != Some(value(i)) {
+                    lost += 1;
+                }
+            }
+            Ok(lost)
+        };
         
+        let engine = open(path, &self.engine_config)?;
+        let write = |batch: &str| -> Result<(), Box<dyn std::error::Error>> {
+            for i in 0..KEYS {
+                engine.put(&key(batch, i), &value(i))?;
+            }
+            Ok(())
+        };
+        write("flushed")?;
         engine.flush()?;
+        write("unflushed")?;
+        let crashed = crash("unflushed")?;
+        let lost_after_flush = lost(crashed.as_ref(), "flushed")?;
+        let lost_without_flush = lost(crashed.as_ref(), "unflushed")?;
+        drop(crashed);
         
-        let elapsed = start.elapsed();
-        let throughput = operations as f64 / elapsed.as_secs_f64();
+        let lost_after_flush_window = match engine.background_flush_interval() {
+            Some(interval) => {
+                write("windowed")?;
+                // Two intervals, so a whole background flush starts after the last write
+                thread::sleep(interval * 2);
+                Some(lost(crash("windowed")?.as_ref(), "windowed")?)
+            }
+            None => None,
+        };
+        Ok(DurabilityCheck { keys: KEYS, lost_after_flush, lost_without_flush, lost_after_flush_window })
+    }
+    
+    // Latencies are recorded in microseconds
+    fn new_histogram(&self) -> Result<Histogram<u64>, hdrhistogram::CreationError> {
+        let mut hist = Histogram::<u64>::new_with_bounds(1, self.max_latency.as_micros() as u64, self.latency_sigfigs)?;
+        hist.auto(self.auto_resize_histograms);
+        Ok(hist)
+    }
+    
+    fn run_reader_writer(&self, engine: &dyn StorageEngine, populated: u64) -> Result<ReaderWriterRun, Box<dyn std::error::Error>> {
+        let threads = (self.writer_threads + self.reader_threads) as u64;
+        let ops_per_thread = self.num_operations / threads;
         
-        Ok(BenchmarkResult {
-            engine_name: engine.engine_name().to_string(),
-            throughput,
-            write_p99_ms: write_hist.value_at_percentile(99.0) as f64 / 1000.0,
-            read_p99_ms: read_hist.value_at_percentile(99.0) as f64 / 1000.0,
-            scan_p99_ms: scan_hist.value_at_percentile(99.0) as f64 / 1000.0,
-            metrics: engine.metrics(),
+        let results = thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads).map(|t| {
+                let writer = t < self.writer_threads as u64;
+                scope.spawn(move || -> Result<(Histogram<u64>, Duration), String> {
+                    let mut hist = self.new_histogram().map_err(|e| e.to_string())?;
+                    let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(t + 1));
+                    let start = Instant::now();
+                    for _ in 0..ops_per_thread {
+                        let op_start;
+                        if writer {
+                            let key_num = rng.gen_range(0..self.key_space);
+                            let value = match self.value_seed {
+                                Some(seed) => self.keyed_value(seed, key_num, self.write_value_len()),
+                                None => self.generate_value(&mut rng),
+                            };
+                            let key = self.key(key_num);
+                            op_start = Instant::now();
+                            engine.put(&key, &value).map_err(|e| e.to_string())?;
+                        } else {
+                            let key = self.key(rng.gen_range(0..populated));
+                            op_start = Instant::now();
+                            engine.get(&key).map_err(|e| e.to_string())?;
+                        }
+                        record_latency(&mut hist, op_start.elapsed());
+                    }
+                    Ok((hist, start.elapsed()))
+                })
+            }).collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Result<Vec<_>, String>>()
+        })?;
+        
+        let stats = |(hist, elapsed): &(Histogram<u64>, Duration)| ThreadStats {
+            operations: hist.len(),
+            throughput: hist.len() as f64 / elapsed.as_secs_f64(),
+            p50_ms: hist.value_at_percentile(50.0) as f64 / 1000.0,
+            p99_ms: hist.value_at_percentile(99.0) as f64 / 1000.0,
+        };
+        let combined_p99 = |threads: &[(Histogram<u64>, Duration)]| -> Result<f64, Box<dyn std::error::Error>> {
+            let mut all = self.new_histogram()?;
+            for (hist, _) in threads {
+                all.add(hist)?;
+            }
+            Ok(all.value_at_percentile(99.0) as f64 / 1000.0)
+        };
+        let (writers, readers) = results.split_at(self.writer_threads);
+        let writer_stats: Vec<ThreadStats> = writers.iter().map(stats).collect();
+        let reader_stats: Vec<ThreadStats> = readers.iter().map(stats).collect();
+        Ok(ReaderWriterRun {
+            write_throughput: writer_stats.iter().map(|s| s.throughput).sum(),
+            read_throughput: reader_stats.iter().map(|s| s.throughput).sum(),
+            write_p99_ms: combined_p99(writers)?,
+            read_p99_ms: combined_p99(readers)?,
+            writers: writer_stats,
+            readers: reader_stats,
         })
     }
+    
+    // Writers overwrite a fixed range with single-byte-filled values while a
+    // reader scans it. A scan must return every key once, in order, and every
+    // value whole; anything else counts as a violation. The range spans
+    // column families, so RocksDB's range_scan reads them all at one snapshot.
+    fn check_scan_consistency(&self, engine: &dyn StorageEngine) -> Result<u64, Box<dyn std::error::Error>> {
+        let range = self.scan_length;
+        let key = |i: usize| format!("consistency_{:08}", i).into_bytes();
+        for i in 0..range {
+            engine.put(&key(i), &vec![0u8; self.value_size])?;
+        }
+        // Whichever key the engine orders first
+        let first = (0..range).map(key).min_by(|a, b| engine.compare_keys(a, b)).unwrap_or_default();
+        
+        let done = AtomicBool::new(false);
+        let violations = AtomicU64::new(0);
+        
+        thread::scope(|scope| -> Result<(), Box<dyn std::error::Error>> {
+            let writers: Vec<_> = (0..4u64).map(|w| {
+                let key = &key;
+                scope.spawn(move || -> Result<(), String> {
+                    let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(w + 1));
+                    for _ in 0..5_000 {
+                        let value = vec![rng.gen::<u8>(); self.value_size];
+                        engine.put(&key(rng.gen_range(0..range)), &value)
+                            .map_err(|e| e.to_string())?;
+                    }
+                    Ok(())
+                })
+            }).collect();
+
+            let reader = scope.spawn(|| -> Result<(), String> {
+                while !done.load(Ordering::Relaxed) {
+                    let scanned = engine.range_scan(&first, range)
+                        .map_err(|e| e.to_string())?;
+                    let ordered = scanned.windows(2).all(|w| engine.compare_keys(&w[0].0, &w[1].0).is_lt());
+                    let whole = scanned.iter().all(|(_, v)| {
+                        v.len() == self.value_size && v.iter().all(|b| *b == v[0])
+                    });
+                    if scanned.len() != range || !ordered || !whole {
+                        violations.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                Ok(())
+            });
+
+            let mut result = Ok(());
+            for writer in writers {
+                result = result.and(writer.join().unwrap());
+            }
+            done.store(true, Ordering::Relaxed);
+            result.and(reader.join().unwrap())?;
+            Ok(())
+        })?;
+        
+        Ok(violations.load(Ordering::Relaxed))
+    }
+    
+    // Catches broken engine wiring before it produces meaningless numbers:
+    // writes known pairs, reads them back, deletes every other key and checks
+    // that a scan returns exactly the survivors in order. Leaves no keys behind.
+    fn self_check(engine: &dyn StorageEngine) -> Result<(), Box<dyn std::error::Error>> {
+        let name = engine.engine_name();
+        let key = |i: u32| format!("selfcheck_{:08}", i).into_bytes();
+        let value = |i: u32| format!("value_{}", i).into_bytes();
+        
+        for i in 0..100 {
+            engine.put(&key(i), &value(i))?;
+        }
+        for i in 0..100 {
+            if engine.get(&key(i))? != Some(value(i)) {
+                return Err(format!("{} self-check: key {} did not read back as written", name, i).into());
+            }
+        }
+        for i in (0..100).step_by(2) {
+            engine.delete(&key(i))?;
+        }
+        for i in (0..100).step_by(2) {
+            if engine.get(&key(i))?.is_some() {
+                return Err(format!("{} self-check: key {} still present after delete", name, i).into());
+            }
+        }
+        
+        let mut expected: Vec<_> = (1..100).step_by(2).map(|i| (key(i), value(i))).collect();
+        expected.sort_by(|a, b| engine.compare_keys(&a.0, &b.0));
+        let scanned = engine.range_scan(&expected[0].0, 50)?;
+        if scanned != expected {
+            return Err(format!("{} self-check: range scan returned {} pairs, not the 50 surviving keys in order",
+                name, scanned.len()).into());
+        }
+        
+        for i in (1..100).step_by(2) {
+            engine.delete(&key(i))?;
+        }
+        Ok(())
+    }
 }
 
-pub fn compare_engines() -> Result<(), Box<dyn std::error::Error>> {
+// Runs `benchmark` against every registered engine and prints the comparison.
+// Fails if any result breaks benchmark.sla.
+pub fn compare_engines(benchmark: &Benchmark) -> Result<(), Box<dyn std::error::Error>> {
     println!("B-Tree vs LSM-Tree Comparison\n");
     
-    let benchmark = Benchmark::new();
+    let baseline = match &benchmark.baseline {
+        Some(name) => Some(ENGINES.iter().position(|e| e.name == name)
+            .ok_or_else(|| format!("unknown baseline engine '{}'", name))?),
+        None => None,
+    };
     let mut results = Vec::new();
     
-    let rocksdb_dir = tempfile::tempdir()?;
-    let rocksdb = Arc::new(RocksDBEngine::new(rocksdb_dir.path())?);
-    println!("Benchmarking RocksDB...");
-    results.push(benchmark.run(rocksdb)?);
-    
-    let sled_dir = tempfile::tempdir()?;
-    let sled = Arc::new(SledEngine::new(sled_dir.path())?);
-    println!("Benchmarking Sled...");
-    results.push(benchmark.run(sled)?);
-    
-    println!("\n| Metric | {} | {} | Winner |", results[0].engine_name, results[1].engine_name);
-    println!("|--------|-------|-------|--------|");
-    
-    // Throughput
-    let t_winner = if results[0].throughput > results[1].throughput { 0 } else { 1 };
-    println!("| Throughput | {:.0} ops/s | {:.0} ops/s | {} ({:.1}x) |",
-        results[0].throughput, results[1].throughput,
-        results[t_winner].engine_name.split(' ').next().unwrap(),
-        results[t_winner].throughput / results[1 - t_winner].throughput
-    );
-    
-    // Write latency
-    let w_winner = if results[0].write_p99_ms < results[1].write_p99_ms { 0 } else { 1 };
-    println!("| P99 Write | {:.1}ms | {:.1}ms | {} ({:.1}x) |",
-        results[0].write_p99_ms, results[1].write_p99_ms,
-        results[w_winner].engine_name.split(' ').next().unwrap(),
-        results[1 - w_winner].write_p99_ms / results[w_winner].write_p99_ms
-    );
-    
-    // Read latency
-    let r_winner = if results[0].read_p99_ms < results[1].read_p99_ms { 0 } else { 1 };
-    println!("| P99 Read | {:.1}ms | {:.1}ms | {} ({:.1}x) |",
-        results[0].read_p99_ms, results[1].read_p99_ms,
-        results[r_winner].engine_name.split(' ').next().unwrap(),
-        results[1 - r_winner].read_p99_ms / results[r_winner].read_p99_ms
-    );
-    
-    // Range scan
-    let s_winner = if results[0].scan_p99_ms < results[1].scan_p99_ms { 0 } else { 1 };
-    println!("| P99 Scan | {:.1}ms | {:.1}ms | {} ({:.1}x) |",
-        results[0].scan_p99_ms, results[1].scan_p99_ms,
-        results[s_winner].engine_name.split(' ').next().unwrap(),
-        results[1 - s_winner].scan_p99_ms / results[s_winner].scan_p99_ms
-    );
-    
-    // Write amplification
-    let wa_winner = if results[0].metrics.write_amplification < results[1].metrics.write_amplification { 0 } else { 1 };
-    println!("| Write Amp | {:.1}x | {:.1}x | {} ({:.1}x) |",
-        results[0].metrics.write_amplification, results[1].metrics.write_amplification,
-        results[wa_winner].engine_name.split(' ').next().unwrap(),
-        results[1 - wa_winner].metrics.write_amplification / results[wa_winner].metrics.write_amplification
-    );
-    
-    // Space amplification
-    let sa_winner = if results[0].metrics.space_amplification < results[1].metrics.space_amplification { 0 } else { 1 };
-    println!("| Space Amp | {:.1}x | {:.1}x | {} ({:.1}x) |",
-        results[0].metrics.space_amplification, results[1].metrics.space_amplification,
-        results[sa_winner].engine_name.split(' ').next().unwrap(),
-        results[1 - sa_winner].metrics.space_amplification / results[sa_winner].metrics.space_amplification
-    );
+    for entry in ENGINES {
+        println!("Benchmarking {}...", entry.name);
+        results.push(benchmark.run_repeated(entry.open)?);
+    }
+    
+    for result in &results {
+        println!("{} version: {}", result.engine_name, result.engine_version.as_deref().unwrap_or("unknown"));
+    }
+    
+    for result in &results {
+        let limit = match result.stop_reason {
+            StopReason::OperationCount => "operation count",
+            StopReason::Duration => "duration",
+            StopReason::DiskSpace => "free disk space",
+            StopReason::DatasetSize => "dataset size",
+            StopReason::Converged => "convergence",
+        };
+        println!("{} stopped on {} limit (seed {}, dataset {:016x})",
+            result.engine_name, limit, result.seed, result.dataset_fingerprint);
+        if let Some(rate) = benchmark.rate_limit {
+            println!("{} sustained {:.0} of {:.0} target ops/s ({:.1}%)",
+                result.engine_name, result.throughput, rate, result.throughput / rate * 100.0);
+        }
+        if let Some(secs) = result.converged_after_secs {
+            println!("{} throughput converged after {:.1}s", result.engine_name, secs);
+        }
+        for phase in &result.phases {
+            println!("{} {} phase: {} ops in {:.2}s ({:.0} ops/s, P99 {:.2}ms)",
+                result.engine_name, phase.name, phase.operations, phase.elapsed_secs, phase.throughput, phase.p99_ms);
+        }
+        if let Some(load) = result.load {
+            let detail = match load.write_p99_ms {
+                Some(p99) => format!("P99 {:.2}ms", p99),
+                None => "bulk".to_string(),
+            };
+            println!("{} loaded {} keys in {:.2}s ({:.0} ops/s, {})",
+                result.engine_name, load.keys, load.elapsed_secs, load.throughput, detail);
+        }
+        if let Some(compaction) = result.full_compaction {
+            println!("{} full compaction took {:.2}s; read P99 {:.3}ms before, {:.3}ms after",
+                result.engine_name, compaction.elapsed_secs, compaction.read_p99_before_ms, compaction.read_p99_after_ms);
+        }
+        if let Some(open_latency) = result.open_latency_ms {
+            println!("{} opened in {:.1}ms", result.engine_name, open_latency);
+        }
+        if let Some(recovery) = result.recovery_ms {
+            println!("{} reopened (recovery) in {:.1}ms", result.engine_name, recovery);
+        }
+        if result.saturated_samples > 0 {
+            println!("{} had {} latencies above the {:?} histogram limit; raise max_latency or set auto_resize_histograms",
+                result.engine_name, result.saturated_samples, benchmark.max_latency);
+        }
+        if let Some(check) = result.durability {
+            println!("{} durability: {}/{} flushed and {}/{} unflushed keys lost in a simulated crash",
+                result.engine_name, check.lost_after_flush, check.keys, check.lost_without_flush, check.keys);
+            if let Some(lost) = check.lost_after_flush_window {
+                println!("{} durability: {}/{} unflushed keys lost after the background flush interval",
+                    result.engine_name, lost, check.keys);
+            }
+        }
+        if let Some(selectivity) = result.scan_selectivity {
+            println!("{} filtered scans: {}/{} rows matched ({:.1}%), {:.0} matching rows/s",
+                result.engine_name, selectivity.matched, selectivity.scanned,
+                selectivity.matched as f64 / selectivity.scanned.max(1) as f64 * 100.0, selectivity.matched_per_sec);
+        }
+        if let Some(run) = &result.reader_writer {
+            println!("{} with {} writer / {} reader threads: writes {:.0} ops/s (P99 {:.2}ms), reads {:.0} ops/s (P99 {:.2}ms)",
+                result.engine_name, run.writers.len(), run.readers.len(),
+                run.write_throughput, run.write_p99_ms, run.read_throughput, run.read_p99_ms);
+            let threads = run.writers.iter().map(|t| ("writer", t)).chain(run.readers.iter().map(|t| ("reader", t)));
+            for (i, (role, thread)) in threads.enumerate() {
+                println!("  thread {} ({}): {} ops, {:.0} ops/s, P50 {:.3}ms, P99 {:.3}ms",
+                    i, role, thread.operations, thread.throughput, thread.p50_ms, thread.p99_ms);
+            }
+        }
+        if let Some(violations) = result.consistency_violations {
+            println!("{} scan consistency violations: {}", result.engine_name, violations);
+        }
+        if let Some(check) = result.staleness {
+            println!("{} stale reads after write: {}/{} (max lag {} versions)",
+                result.engine_name, check.stale_reads, check.reads, check.max_lag);
+        }
+        if let Some(cpu) = result.cpu_time {
+            println!("{} CPU time per op: P50 {:.3}ms, P99 {:.3}ms ({:.0}% of latency on CPU)",
+                result.engine_name, cpu.p50_ms, cpu.p99_ms, cpu.cpu_fraction * 100.0);
+        }
+        if let Some(stalls) = result.write_stalls {
+            println!("{} write stalls: {} (about {:.2}s stalled)", result.engine_name, stalls.count, stalls.estimated_secs);
+        }
+        if let Some(scan) = result.full_scan {
+            println!("{} full scan: {} keys in {:.2}s ({:.0} keys/s, {:.1} MB/s)",
+                result.engine_name, scan.keys, scan.elapsed_secs, scan.keys_per_sec, scan.mb_per_sec);
+        }
+        if let Some(log) = &result.engine_log {
+            println!("{} log: {} flushes, {} compactions, {} stall events",
+                result.engine_name, log.flushes, log.compactions, log.stalls.len());
+            for stall in &log.stalls {
+                println!("  {} {}", stall.timestamp, stall.reason);
+            }
+        }
+        if let Some(check) = result.ttl {
+            let mb = |bytes: Option<u64>| bytes.map_or("n/a".to_string(), |b| format!("{:.1}MB", b as f64 / 1024.0 / 1024.0));
+            println!("{} TTL: {}/{} keys readable after expiry; data dir {} before, {} after purge",
+                result.engine_name, check.readable_after_expiry, check.keys,
+                mb(check.size_before_bytes), mb(check.size_after_bytes));
+        }
+        if let Some(stats) = result.checkpoints {
+            println!("{} took {} checkpoints (mean {:.1}ms, max {:.1}ms); {:.0} ops/s during vs {:.0} ops/s between",
+                result.engine_name, stats.count, stats.mean_ms, stats.max_ms, stats.throughput_during, stats.throughput_between);
+        }
+        if let Some(summary) = &result.summary {
+            println!("{} across {} runs (mean ± stddev, min–max):", result.engine_name, summary.runs);
+            for (label, stats) in [
+                ("Throughput ops/s", summary.throughput),
+                ("P99 Write ms", summary.write_p99_ms),
+                ("P99 Read ms", summary.read_p99_ms),
+                ("P99 Scan ms", summary.scan_p99_ms),
+            ] {
+                println!("  {}: {:.2} ± {:.2} ({:.2}–{:.2})",
+                    label, stats.mean, stats.stddev, stats.min, stats.max);
+            }
+        }
+    }
+    
+    if benchmark.engine_config.disable_wal {
+        println!("\nNON-DURABLE: write-ahead logging / background flushing disabled; these are ceiling numbers, not real ones");
+    }
+    if benchmark.scan_ratio > 0 {
+        let readahead = benchmark.engine_config.scan_readahead
+            .map_or("default".to_string(), |bytes| format!("{}KB", bytes / 1024));
+        println!("\nScan readahead (RocksDB): {}, scans of {} keys", readahead, benchmark.scan_length);
+    }
+    let mut report = ComparisonReport::new(&results);
+    
+    // Throughput, overall and per operation type
+    let mut throughput_rows: Vec<(&str, Metric)> = vec![
+        ("Throughput", |r| r.throughput),
+        ("Write Throughput", |r| r.write_throughput),
+        ("Read Throughput", |r| r.read_throughput),
+        ("Scan Throughput", |r| r.scan_throughput),
+    ];
+    if benchmark.flush_interval_ops > 0 {
+        throughput_rows.insert(1, ("Throughput excl. Flush", |r| r.throughput_excluding_flush));
+    }
+    if benchmark.merge_ratio > 0 {
+        throughput_rows.push(("Merge Throughput", |r| r.merge_throughput));
+    }
+    if benchmark.prefix_scan_ratio > 0 {
+        throughput_rows.push(("Prefix Scan Throughput", |r| r.prefix_scan_throughput));
+    }
+    if benchmark.txn_ratio > 0 {
+        throughput_rows.push(("Txn Throughput", |r| r.txn_throughput));
+    }
+    if benchmark.multi_get_ratio > 0 {
+        throughput_rows.push(("Multi-Get Throughput", |r| r.multi_get_throughput));
+    }
+    for (label, metric) in throughput_rows {
+        report.metric_row(&results, label, metric, 0, " ops/s", true);
+    }
+    
+    // Tail latency; read hits/misses and inserts/updates only when the workload splits them
+    let mut latency_rows: Vec<(&str, Metric)> = vec![
+        ("P99 Write", |r| r.write_p99_ms),
+        ("P99 Read", |r| r.read_p99_ms),
+    ];
+    if benchmark.read_miss_ratio > 0.0 {
+        latency_rows.push(("P99 Read Hit", |r| r.read_hit_p99_ms));
+        latency_rows.push(("P99 Read Miss", |r| r.read_miss_p99_ms));
+    }
+    if benchmark.update_ratio.is_some() {
+        latency_rows.push(("P99 Insert", |r| r.insert_p99_ms));
+        latency_rows.push(("P99 Update", |r| r.update_p99_ms));
+    }
+    latency_rows.push(("P99 Scan", |r| r.scan_p99_ms));
+    if benchmark.merge_ratio > 0 {
+        latency_rows.push(("P99 Merge", |r| r.merge_p99_ms));
+    }
+    if benchmark.prefix_scan_ratio > 0 {
+        latency_rows.push(("P99 Prefix Scan", |r| r.prefix_scan_p99_ms));
+    }
+    if benchmark.txn_ratio > 0 {
+        latency_rows.push(("P99 Txn Commit", |r| r.txn_commit_p99_ms));
+    }
+    if benchmark.multi_get_ratio > 0 {
+        // Per batch
+        latency_rows.push(("P99 Multi-Get", |r| r.multi_get_p99_ms));
+    }
+    for (label, metric) in latency_rows {
+        report.metric_row(&results, label, metric, 1, "ms", false);
+    }
+    
+    // Amplification and compression
+    report.metric_row(&results, "Write Amp", |r| r.metrics.write_amplification, 1, "x", false);
+    report.metric_row(&results, "Space Amp", |r| r.metrics.space_amplification, 1, "x", false);
+    report.metric_row(&results, "Peak Space Amp", |r| r.peak_space_amplification, 1, "x", false);
+    report.metric_row(&results, "Compression", |r| r.metrics.compression_ratio, 1, "x", true);
+    
+    // Raw bytes written to disk, which is what wears out SSDs
+    report.info_row(&results, "Disk Written", |r| r.disk_bytes_written
+        .map_or("n/a".to_string(), |bytes| format!("{:.2}GB", bytes as f64 / 1024.0 / 1024.0 / 1024.0)));
+    report.info_row(&results, "Disk Bytes/Op", |r| r.disk_bytes_written
+        .map_or("n/a".to_string(), |bytes| format!("{:.0}B", bytes as f64 / r.operations.max(1) as f64)));
+    
+    // Operational footprint, for fd limits and deployment sizing
+    report.info_row(&results, "Peak Open FDs", |r| r.peak_open_fds.map_or("n/a".to_string(), |fds| fds.to_string()));
+    report.info_row(&results, "Peak Threads", |r| r.peak_threads.map_or("n/a".to_string(), |threads| threads.to_string()));
+    
+    // Cache hit rate
+    report.info_row(&results, "Cache Hit Rate", |r| r.metrics.cache_hit_rate
+        .map_or("n/a".to_string(), |rate| format!("{:.1}%", rate * 100.0)));
+    report.info_row(&results, "Bloom FP Rate", |r| r.metrics.bloom_false_positive_rate
+        .map_or("n/a".to_string(), |rate| format!("{:.2}%", rate * 100.0)));
     
     // Memory usage
-    let m_winner = if results[0].metrics.memory_usage_mb < results[1].metrics.memory_usage_mb { 0 } else { 1 };
-    println!("| Memory | {:.1}MB | {:.1}MB | {} ({:.1}x) |",
-        results[0].metrics.memory_usage_mb, results[1].metrics.memory_usage_mb,
-        results[m_winner].engine_name.split(' ').next().unwrap(),
-        results[1 - m_winner].metrics.memory_usage_mb / results[m_winner].metrics.memory_usage_mb
-    );
+    report.metric_row(&results, "Memory", |r| r.metrics.memory_usage_mb, 1, "MB", false);
+    
+    report.print();
+    if let Some(path) = &benchmark.compare_output {
+        fs::write(path, report.to_json())?;
+    }
+    
+    if let Some(baseline) = baseline {
+        print_relative_table(&results, baseline);
+    }
+    
+    // Whether throughput differences across repeated runs are more than noise
+    let samples: Vec<&[f64]> = results.iter()
+        .filter_map(|r| r.summary.as_ref().map(|s| s.throughput_samples.as_slice()))
+        .collect();
+    if samples.len() == results.len() && results.len() > 1 {
+        println!("\nThroughput significance (Mann-Whitney U, alpha {}):", benchmark.significance_alpha);
+        for i in 0..results.len() {
+            for j in i + 1..results.len() {
+                let test = mann_whitney_u(samples[i], samples[j]);
+                println!("  {} vs {}: U={:.1}, p={:.4}, {}",
+                    results[i].engine_name, results[j].engine_name, test.u, test.p_value,
+                    if test.p_value < benchmark.significance_alpha { "significant" } else { "not significant" });
+            }
+        }
+    }
     
     // Compaction
     println!("\nCompaction overhead:");
-    println!("  {}: {:.1}MB read, {:.1}MB written", 
-        results[0].engine_name,
-        results[0].metrics.compaction_stats.0 as f64 / 1024.0 / 1024.0,
-        results[0].metrics.compaction_stats.1 as f64 / 1024.0 / 1024.0
-    );
-    println!("  {}: {:.1}MB read, {:.1}MB written",
-        results[1].engine_name,
-        results[1].metrics.compaction_stats.0 as f64 / 1024.0 / 1024.0,
-        results[1].metrics.compaction_stats.1 as f64 / 1024.0 / 1024.0
-    );
+    for result in &results {
+        println!("  {}: {:.1}MB read, {:.1}MB written",
+            result.engine_name,
+            result.metrics.compaction_stats.0 as f64 / 1024.0 / 1024.0,
+            result.metrics.compaction_stats.1 as f64 / 1024.0 / 1024.0
+        );
+    }
+    
+    // LSM shape, for engines that have levels
+    for result in results.iter().filter(|r| !r.metrics.levels.is_empty()) {
+        let pending = match result.metrics.compaction_pending {
+            Some(true) => "yes",
+            Some(false) => "no",
+            None => "unknown",
+        };
+        println!("\nLSM levels for {} (compaction pending: {}):", result.engine_name, pending);
+        println!("| Level | Files | Size (MB) |");
+        println!("|-------|-------|-----------|");
+        for level in &result.metrics.levels {
+            println!("| L{} | {} | {:.1} |", level.level, level.files, level.size_mb);
+        }
+    }
+    
+    // Key distribution across column families / trees
+    for result in results.iter().filter(|r| !r.metrics.column_families.is_empty()) {
+        println!("\nColumn families for {}:", result.engine_name);
+        println!("| Name | Keys | Size (MB) |");
+        println!("|------|------|-----------|");
+        for cf in &result.metrics.column_families {
+            let size = cf.size_mb.map_or("n/a".to_string(), |mb| format!("{:.1}", mb));
+            println!("| {} | {} | {} |", cf.name, cf.keys, size);
+        }
+    }
+    
+    // Write latency by value size, once writes span more than one bucket
+    for result in &results {
+        if result.write_size_buckets.iter().filter(|b| b.writes > 0).count() > 1 {
+            println!("\nWrite latency by value size for {}:", result.engine_name);
+            println!("| Size | Writes | P99 (ms) |");
+            println!("|------|--------|----------|");
+            for bucket in result.write_size_buckets.iter().filter(|b| b.writes > 0) {
+                println!("| {} | {} | {:.3} |", bucket.label, bucket.writes, bucket.p99_ms);
+            }
+        }
+    }
+    
+    // Latency burst by burst, and whether the idle time after each let compaction finish
+    for result in results.iter().filter(|r| !r.bursts.is_empty()) {
+        println!("\nBursts for {} (overall P99 write {:.3}ms, read {:.3}ms):",
+            result.engine_name, result.write_p99_ms, result.read_p99_ms);
+        println!("| Start (s) | Ops | P50 (ms) | P99 (ms) | Compaction Pending After Idle |");
+        println!("|-----------|-----|----------|----------|-------------------------------|");
+        for burst in &result.bursts {
+            let pending = match burst.compaction_pending_after_idle {
+                Some(true) => "yes",
+                Some(false) => "no",
+                None => "unknown",
+            };
+            println!("| {:.1} | {} | {:.3} | {:.3} | {} |", burst.start_secs, burst.operations, burst.p50_ms, burst.p99_ms, pending);
+        }
+    }
+    
+    // Tail latency over the course of the run
+    for result in results.iter().filter(|r| !r.latency_windows.is_empty()) {
+        println!("\nLatency over time for {}:", result.engine_name);
+        println!("| Start (s) | Ops | Keys | P50 (ms) | P99 (ms) |");
+        println!("|-----------|-----|------|----------|----------|");
+        for window in &result.latency_windows {
+            println!("| {:.1} | {} | {} | {:.3} | {:.3} |",
+                window.start_secs, window.operations, window.keys, window.p50_ms, window.p99_ms);
+        }
+        if let Some(scaling) = result.latency_scaling {
+            println!("P99 vs key count: r = {:.2}, {:+.3}ms per million keys",
+                scaling.correlation, scaling.p99_ms_per_million_keys);
+        }
+    }
+    
+    // Latency CDF, reconstructed from the serialized histograms
+    println!("\nLatency distribution (ms):");
+    print_latency_cdf("Write", results.iter().map(|r| r.write_histogram.as_str()))?;
+    print_latency_cdf("Read", results.iter().map(|r| r.read_histogram.as_str()))?;
+    print_latency_cdf("Scan", results.iter().map(|r| r.scan_histogram.as_str()))?;
+    
+    // One greppable line per engine on stderr, apart from the report on stdout
+    for result in &results {
+        eprintln!("SUMMARY engine={} throughput={:.0} write_p99={:.3} read_p99={:.3} scan_p99={:.3} durable={}",
+            result.engine_name.split(' ').next().unwrap(),
+            result.throughput, result.write_p99_ms, result.read_p99_ms, result.scan_p99_ms,
+            !benchmark.engine_config.disable_wal
+        );
+    }
+    
+    let violations: Vec<String> = results.iter()
+        .flat_map(|r| benchmark.sla.violations(r))
+        .collect();
+    if !violations.is_empty() {
+        eprintln!("\nSLA violations:");
+        for violation in &violations {
+            eprintln!("  {}", violation);
+        }
+        return Err(format!("{} SLA violation(s)", violations.len()).into());
+    }
+    
+    Ok(())
+}
+
+pub type Metric = fn(&BenchmarkResult) -> f64;
+
+// Margin below which the best engine is reported as tied with the runner-up
+const TIE_MARGIN: f64 = 1.01;
+
+// The comparison table as data, so it can be saved with compare_output
+// and printed again, via from_json, without rerunning the benchmark
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonReport {
+    pub engines: Vec<String>,
+    pub rows: Vec<ComparisonRow>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonRow {
+    pub metric: String,
+    pub cells: Vec<String>, // one formatted value per engine
+    pub values: Vec<f64>, // the raw values; empty for informational rows
+    pub verdict: Verdict,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    NotRanked, // informational row, or a single engine
+    Tie, // best and runner-up within TIE_MARGIN
+    Winner { engine: String, margin: f64 }, // margin over the runner-up
+}
+
+impl ComparisonReport {
+    pub fn new(results: &[BenchmarkResult]) -> Self {
+        Self { engines: results.iter().map(|r| r.engine_name.clone()).collect(), rows: Vec::new() }
+    }
+    
+    // A ranked row; results must be in the same order as the report's engines
+    pub fn metric_row(&mut self, results: &[BenchmarkResult], label: &str, metric: Metric, precision: usize, unit: &str, higher_is_better: bool) {
+        let mut ranked: Vec<&BenchmarkResult> = results.iter().collect();
+        ranked.sort_by(|a, b| metric(a).total_cmp(&metric(b)));
+        if higher_is_better {
+            ranked.reverse();
+        }
+        let verdict = match ranked.as_slice() {
+            [best, runner_up, ..] => {
+                let (best_value, runner_up_value) = (metric(best), metric(runner_up));
+                let margin = if higher_is_better { best_value / runner_up_value } else { runner_up_value / best_value };
+                // Two zeroes give NaN, which also counts as a tie
+                if margin.is_nan() || margin < TIE_MARGIN {
+                    Verdict::Tie
+                } else {
+                    Verdict::Winner { engine: best.engine_name.clone(), margin }
+                }
+            }
+            _ => Verdict::NotRanked,
+        };
+        self.rows.push(ComparisonRow {
+            metric: label.to_string(),
+            cells: results.iter().map(|r| format!("{:.*}{}", precision, metric(r), unit)).collect(),
+            values: results.iter().map(metric).collect(),
+            verdict,
+        });
+    }
+    
+    // A row with no winner, for values that aren't better or worse
+    pub fn info_row(&mut self, results: &[BenchmarkResult], label: &str, cell: impl Fn(&BenchmarkResult) -> String) {
+        self.rows.push(ComparisonRow {
+            metric: label.to_string(),
+            cells: results.iter().map(cell).collect(),
+            values: Vec::new(),
+            verdict: Verdict::NotRanked,
+        });
+    }
+    
+    // The winner column names the best engine and its margin over the
+    // runner-up, or "tie" when they are within TIE_MARGIN
+    pub fn print(&self) {
+        print!("\n| Metric |");
+        for engine in &self.engines {
+            print!(" {} |", engine);
+        }
+        println!(" Winner |\n|--------|{}--------|", "-------|".repeat(self.engines.len()));
+        for row in &self.rows {
+            print!("| {} |", row.metric);
+            for cell in &row.cells {
+                print!(" {} |", cell);
+            }
+            let winner = match &row.verdict {
+                Verdict::NotRanked => "-".to_string(),
+                Verdict::Tie => "tie".to_string(),
+                Verdict::Winner { engine, margin } => format!("{} ({:.1}x)", engine.split(' ').next().unwrap(), margin),
+            };
+            println!(" {} |", winner);
+        }
+    }
+    
+    pub fn to_json(&self) -> String {
+        let strings = |items: &[String]| items.iter().map(|s| json::string(s)).collect::<Vec<_>>().join(",");
+        // JSON has no NaN or infinity
+        let number = |n: f64| if n.is_finite() { n.to_string() } else { "null".to_string() };
+        let rows: Vec<String> = self.rows.iter().map(|row| {
+            let (winner, margin) = match &row.verdict {
+                Verdict::NotRanked => ("null".to_string(), "null".to_string()),
+                Verdict::Tie => ("\"tie\"".to_string(), "null".to_string()),
+                Verdict::Winner { engine, margin } => (json::string(engine), number(*margin)),
+            };
+            format!("{{\"metric\":{},\"cells\":[{}],\"values\":[{}],\"winner\":{},\"margin\":{}}}",
+                json::string(&row.metric), strings(&row.cells),
+                row.values.iter().map(|&v| number(v)).collect::<Vec<_>>().join(","),
+                winner, margin)
+        }).collect();
+        format!("{{\"engines\":[{}],\"rows\":[{}]}}\n", strings(&self.engines), rows.join(","))
+    }
     
+    // Reads back what to_json wrote; non-finite values come back as NaN
+    pub fn from_json(input: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let strings = |value: &Json| -> Result<Vec<String>, String> {
+            value.as_array()?.iter().map(|item| item.as_str().map(str::to_string)).collect()
+        };
+        let report = json::parse(input)?;
+        let rows = report.get("rows")?.as_array()?.iter().map(|row| -> Result<ComparisonRow, String> {
+            let verdict = match row.get("winner")? {
+                Json::Null => Verdict::NotRanked,
+                Json::String(winner) if winner == "tie" => Verdict::Tie,
+                winner => Verdict::Winner { engine: winner.as_str()?.to_string(), margin: row.get("margin")?.as_f64()? },
+            };
+            Ok(ComparisonRow {
+                metric: row.get("metric")?.as_str()?.to_string(),
+                cells: strings(row.get("cells")?)?,
+                values: row.get("values")?.as_array()?.iter().map(Json::as_f64).collect::<Result<_, _>>()?,
+                verdict,
+            })
+        }).collect::<Result<_, _>>()?;
+        Ok(Self { engines: strings(report.get("engines")?)?, rows })
+    }
+}
+
+// Every engine's metrics as a multiple of the baseline engine's, so the
+// baseline column reads 1.00x throughout
+fn print_relative_table(results: &[BenchmarkResult], baseline: usize) {
+    println!("\nRelative to {}:", results[baseline].engine_name);
+    print!("| Metric |");
+    for result in results {
+        print!(" {} |", result.engine_name);
+    }
+    println!("\n|--------|{}", "-------|".repeat(results.len()));
+    
+    let rows: [(&str, Metric); 7] = [
+        ("Throughput", |r| r.throughput),
+        ("P99 Write", |r| r.write_p99_ms),
+        ("P99 Read", |r| r.read_p99_ms),
+        ("P99 Scan", |r| r.scan_p99_ms),
+        ("Write Amp", |r| r.metrics.write_amplification),
+        ("Space Amp", |r| r.metrics.space_amplification),
+        ("Memory", |r| r.metrics.memory_usage_mb),
+    ];
+    for (label, metric) in rows {
+        let base = metric(&results[baseline]);
+        print!("| {} |", label);
+        for result in results {
+            print!(" {:.2}x |", metric(result) / base);
+        }
+        println!();
+    }
+}
+
+fn print_latency_cdf<'a>(op: &str, encoded: impl Iterator<Item = &'a str>) -> Result<(), Box<dyn std::error::Error>> {
+    let hists = encoded.map(decode_histogram).collect::<Result<Vec<_>, _>>()?;
+    println!("  {}:", op);
+    for percentile in [50.0, 90.0, 99.0, 99.9, 99.99, 100.0] {
+        print!("    P{:<6}", percentile);
+        for hist in &hists {
+            print!(" {:>8.3}", hist.value_at_percentile(percentile) as f64 / 1000.0);
+        }
+        println!();
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod validate_tests {
+    use super::*;
+    
+    fn with_ratios(write_ratio: u32, scan_ratio: u32, merge_ratio: u32) -> Benchmark {
+        let mut benchmark = Benchmark::new();
+        benchmark.write_ratio = write_ratio;
+        benchmark.scan_ratio = scan_ratio;
+        benchmark.merge_ratio = merge_ratio;
+        benchmark
+    }
+    
+    #[test]
+    fn empty_key_space_is_rejected() {
+        let mut benchmark = Benchmark::new();
+        benchmark.key_space = 0;
+        benchmark.prepopulate_keys = 0;
+        assert_eq!(benchmark.validate().unwrap_err().to_string(), "key_space must hold at least one key");
+    }
+    
+    #[test]
+    fn prepopulating_past_the_key_space_is_rejected() {
+        let mut benchmark = Benchmark::new();
+        benchmark.key_space = 1_000;
+        benchmark.prepopulate_keys = 1_000;
+        assert!(benchmark.validate().is_ok());
+        benchmark.prepopulate_keys = 1_001;
+        assert_eq!(benchmark.validate().unwrap_err().to_string(), "prepopulate_keys 1001 is more than the key_space of 1000");
+    }
+    
+    #[test]
+    fn rates_too_slow_to_wait_for_are_rejected() {
+        let mut benchmark = Benchmark::new();
+        for rate in [0.0, -1.0, 1e-300, f64::NAN] {
+            benchmark.rate_limit = Some(rate);
+            assert_eq!(benchmark.validate().unwrap_err().to_string(), format!("rate_limit must be at least 0.001 ops/s, got {}", rate));
+        }
+        benchmark.rate_limit = Some(MIN_RATE);
+        assert!(benchmark.validate().is_ok());
+    }
+    
+    #[test]
+    fn key_sizes_past_the_trace_limit_are_rejected() {
+        let mut benchmark = Benchmark::new();
+        benchmark.key_size = KeySizeDistribution::LogNormal { median: 64, sigma: 3.0, max: MAX_KEY_LEN };
+        assert!(benchmark.validate().is_ok());
+        benchmark.key_size = KeySizeDistribution::LogNormal { median: 64, sigma: 3.0, max: MAX_KEY_LEN + 1 };
+        assert_eq!(benchmark.validate().unwrap_err().to_string(), "key_size allows keys of 65536 bytes, more than the 65535 a trace can record");
+        benchmark.key_size = KeySizeDistribution::Uniform { min: 16, max: 100_000 };
+        assert!(benchmark.validate().is_err());
+    }
+    
+    #[test]
+    fn log_normal_key_sizes_are_clamped() {
+        let sizes = KeySizeDistribution::LogNormal { median: 64, sigma: 4.0, max: 1_024 };
+        let lens: Vec<usize> = (0..10_000).filter_map(|n| sizes.len_for(n)).collect();
+        assert!(lens.iter().all(|&len| len <= 1_024));
+        assert!(lens.contains(&1_024), "sigma 4 should reach the clamp");
+    }
+    
+    #[test]
+    fn ratios_under_100_leave_the_rest_to_reads() {
+        assert!(with_ratios(50, 10, 5).validate().is_ok());
+    }
+    
+    #[test]
+    fn ratios_summing_to_exactly_100_are_accepted() {
+        assert!(with_ratios(70, 20, 10).validate().is_ok());
+    }
+    
+    #[test]
+    fn ratios_over_100_are_rejected_with_the_breakdown() {
+        let err = with_ratios(80, 25, 5).validate().unwrap_err();
+        assert_eq!(err.to_string(), "operation ratios sum to 110, 10 over 100 (reads take whatever is left below 100): \
+            write=80 + scan=25 + merge=5 + prefix_scan=0 + txn=0 + multi_get=0");
+    }
+}
+
+#[cfg(test)]
+mod durability_tests {
+    use super::*;
+    
+    fn check_sled(config: EngineConfig) -> DurabilityCheck {
+        let dir = tempfile::tempdir().unwrap();
+        let mut benchmark = Benchmark::new();
+        benchmark.engine_config = config;
+        let open = |path: &Path, config: &EngineConfig| -> Result<Arc<dyn StorageEngine>, Box<dyn std::error::Error>> {
+            Ok(Arc::new(SledEngine::new(path, config)?))
+        };
+        benchmark.check_durability(&open, dir.path()).unwrap()
+    }
+    
+    #[test]
+    fn unflushed_writes_are_lost_in_a_crash() {
+        let check = check_sled(EngineConfig { disable_wal: true, ..EngineConfig::default() });
+        assert_eq!(check.lost_after_flush, 0);
+        // sled may write out some full buffers on its own, but not the tail
+        assert!(check.lost_without_flush > 0, "no unflushed keys lost");
+        assert_eq!(check.lost_after_flush_window, None);
+    }
+    
+    #[test]
+    fn background_flush_persists_writes_after_its_interval() {
+        let check = check_sled(EngineConfig::default());
+        assert_eq!(check.lost_after_flush, 0);
+        assert_eq!(check.lost_after_flush_window, Some(0));
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::MockEngine;
+    
+    fn small_benchmark() -> Benchmark {
+        let mut benchmark = Benchmark::new();
+        benchmark.key_space = 1_000;
+        benchmark.prepopulate_keys = 500;
+        benchmark.num_operations = 4_000;
+        benchmark.flush_interval_ops = 1_000;
+        benchmark.value_size = 64;
+        benchmark
+    }
+    
+    #[test]
+    fn run_follows_the_operation_mix() {
+        let benchmark = small_benchmark();
+        let engine = Arc::new(MockEngine::new());
+        let result = benchmark.run(engine.clone()).unwrap();
+        let counts = engine.op_counts();
+        
+        // Every measured operation reaches the engine exactly once
+        let writes = counts.puts - benchmark.prepopulate_keys;
+        assert_eq!(result.operations, benchmark.num_operations);
+        assert_eq!(writes + counts.gets + counts.scans, result.operations);
+        assert_eq!(counts.deletes, 0);
+        // Load, every flush_interval_ops, and once after the run
+        assert_eq!(counts.flushes, 1 + benchmark.num_operations / benchmark.flush_interval_ops + 1);
+        
+        let share = |count: u64| count as f64 * 100.0 / result.operations as f64;
+        assert!((share(writes) - benchmark.write_ratio as f64).abs() < 3.0, "write share {}", share(writes));
+        assert!((share(counts.scans) - benchmark.scan_ratio as f64).abs() < 3.0, "scan share {}", share(counts.scans));
+        let read_ratio = 100 - benchmark.write_ratio - benchmark.scan_ratio;
+        assert!((share(counts.gets) - read_ratio as f64).abs() < 3.0, "read share {}", share(counts.gets));
+    }
+    
+    #[test]
+    fn run_sums_per_operation_metrics() {
+        let benchmark = small_benchmark();
+        let engine = Arc::new(MockEngine::new());
+        let result = benchmark.run(engine.clone()).unwrap();
+        
+        // Each per-operation throughput shares the run's elapsed time
+        let per_op = result.write_throughput + result.read_throughput + result.scan_throughput
+            + result.merge_throughput + result.prefix_scan_throughput + result.txn_throughput + result.multi_get_throughput;
+        assert!((per_op - result.throughput).abs() <= result.throughput * 1e-9, "{} vs {}", per_op, result.throughput);
+        assert_eq!(result.stop_reason, StopReason::OperationCount);
+        
+        // Bytes written cover the load plus every measured write, key and value
+        let puts = engine.op_counts().puts;
+        let key_len = benchmark.key(0).len() as u64;
+        assert_eq!(result.metrics.physical_bytes_written, Some(puts * (key_len + benchmark.value_size as u64)));
+        assert_eq!(result.metrics.write_amplification, 1.0);
+    }
+    
+    #[test]
+    fn same_seed_reproduces_the_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut runs = Vec::new();
+        for run in 0..2 {
+            let mut benchmark = small_benchmark();
+            benchmark.seed = 7;
+            let path = dir.path().join(format!("run{}.trace", run));
+            benchmark.trace = Trace::Record(path.clone());
+            let engine = Arc::new(MockEngine::new());
+            let result = benchmark.run(engine.clone()).unwrap();
+            runs.push((fs::read(&path).unwrap(), result.operations, result.dataset_fingerprint,
+                engine.op_counts(), engine.prefix_scan(b"").unwrap()));
+        }
+        assert!(runs[0] == runs[1], "seed 7 produced two different runs");
+        assert!(small_benchmark().verify_reproducible().is_ok());
+    }
+    
+    #[test]
+    fn every_nth_call_fails() {
+        let mut engine = MockEngine::new();
+        engine.fail_every = Some(3);
+        let outcomes: Vec<bool> = (0..9u8).map(|i| engine.put(&[i], b"v").is_ok()).collect();
+        assert_eq!(outcomes, [true, true, false, true, true, false, true, true, false]);
+        // Calls of every kind count towards n
+        assert!(engine.prefix_scan(b"").is_ok());
+        assert!(engine.flush().is_ok());
+        assert_eq!(engine.get(&[0]).unwrap_err().to_string(), "mock get failure (call 12)");
+        // Failed puts never store their key
+        assert_eq!(engine.len(), 6);
+    }
+    
+    #[test]
+    fn run_surfaces_injected_failures() {
+        let benchmark = small_benchmark();
+        // The load makes 500 puts and a flush, so call 600 is in the measured phase
+        let mut engine = MockEngine::new();
+        engine.fail_every = Some(600);
+        let engine = Arc::new(engine);
+        let err = benchmark.run(engine.clone()).unwrap_err();
+        assert!(err.to_string().ends_with("failure (call 600)"), "{}", err);
+        let counts = engine.op_counts();
+        assert_eq!(counts.puts + counts.gets + counts.scans + counts.flushes, 600);
+    }
+    
+    #[test]
+    fn sla_passes_and_fails() {
+        let mut benchmark = small_benchmark();
+        benchmark.num_operations = 200;
+        let mut engine = MockEngine::new();
+        engine.latency.put = Duration::from_millis(2);
+        let result = benchmark.run(Arc::new(engine)).unwrap();
+        
+        benchmark.sla = Sla { max_write_p99_ms: Some(500.0), max_read_p99_ms: Some(500.0), min_throughput: Some(1.0) };
+        assert!(benchmark.sla.violations(&result).is_empty());
+        
+        benchmark.sla = Sla { max_write_p99_ms: Some(1.0), max_read_p99_ms: Some(500.0), min_throughput: Some(1e9) };
+        let violations = benchmark.sla.violations(&result);
+        assert_eq!(violations.len(), 2, "{:?}", violations);
+        assert!(violations[0].starts_with("Mock: write P99"), "{}", violations[0]);
+        assert!(violations[1].starts_with("Mock: throughput"), "{}", violations[1]);
+    }
+}
@@ -0,0 +1,60 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+// Key/value pairs taken from real data, e.g. a production export. The file
+// holds records of key length (u32 LE), key bytes, value length (u32 LE),
+// value bytes; keys and values may be arbitrary binary.
+#[derive(Debug, Clone)]
+pub struct Corpus {
+    records: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Corpus {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut input = BufReader::new(File::open(path)?);
+        let mut records = Vec::new();
+        let mut key_len = [0u8; 4];
+        // A clean end of file is only allowed between records
+        while input.read(&mut key_len[..1])? != 0 {
+            input.read_exact(&mut key_len[1..])?;
+            let key = read_bytes(&mut input, key_len)?;
+            let mut value_len = [0u8; 4];
+            input.read_exact(&mut value_len)?;
+            records.push((key, read_bytes(&mut input, value_len)?));
+        }
+        if records.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("corpus {} has no records", path.display())));
+        }
+        Ok(Self { records })
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    // Key numbers past the end cycle through the corpus again, with the
+    // cycle appended (u32 BE) so every number still maps to a distinct key
+    pub(crate) fn key(&self, n: u64) -> Vec<u8> {
+        let len = self.records.len() as u64;
+        let mut key = self.records[(n % len) as usize].0.clone();
+        if n >= len {
+            key.extend_from_slice(&((n / len) as u32).to_be_bytes());
+        }
+        key
+    }
+
+    pub(crate) fn value(&self, index: usize) -> &[u8] {
+        &self.records[index % self.records.len()].1
+    }
+}
+
+fn read_bytes(input: &mut impl Read, len: [u8; 4]) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
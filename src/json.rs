@@ -0,0 +1,258 @@
+// Just enough JSON to write and read back the files this crate produces,
+// without pulling in a serialization framework
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub(crate) fn get(&self, key: &str) -> Result<&Json, String> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+                .ok_or_else(|| format!("missing field \"{}\"", key)),
+            other => Err(format!("expected an object with \"{}\", got {:?}", key, other)),
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Result<&str, String> {
+        match self {
+            Json::String(s) => Ok(s),
+            other => Err(format!("expected a string, got {:?}", other)),
+        }
+    }
+
+    // null stands in for NaN and infinities, which JSON can't express
+    pub(crate) fn as_f64(&self) -> Result<f64, String> {
+        match self {
+            Json::Number(n) => Ok(*n),
+            Json::Null => Ok(f64::NAN),
+            other => Err(format!("expected a number, got {:?}", other)),
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Result<&[Json], String> {
+        match self {
+            Json::Array(items) => Ok(items),
+            other => Err(format!("expected an array, got {:?}", other)),
+        }
+    }
+}
+
+// A quoted JSON string literal
+pub(crate) fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub(crate) fn parse(input: &str) -> Result<Json, String> {
+    let mut parser = Parser { bytes: input.as_bytes(), pos: 0 };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos != parser.bytes.len() {
+        return Err(format!("trailing characters at byte {}", parser.pos));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.bytes.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn next(&mut self) -> Result<u8, String> {
+        let byte = *self.bytes.get(self.pos).ok_or("unexpected end of input")?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn expect(&mut self, literal: &str) -> Result<(), String> {
+        if !self.bytes[self.pos..].starts_with(literal.as_bytes()) {
+            return Err(format!("expected {} at byte {}", literal, self.pos));
+        }
+        self.pos += literal.len();
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.bytes.get(self.pos).ok_or("unexpected end of input")? {
+            b'n' => self.expect("null").map(|_| Json::Null),
+            b't' => self.expect("true").map(|_| Json::Bool(true)),
+            b'f' => self.expect("false").map(|_| Json::Bool(false)),
+            b'"' => self.string().map(Json::String),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.bytes.get(self.pos) == Some(&b']') {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.next()? {
+                        b',' => continue,
+                        b']' => return Ok(Json::Array(items)),
+                        _ => return Err(format!("expected , or ] at byte {}", self.pos - 1)),
+                    }
+                }
+            }
+            b'{' => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                self.skip_whitespace();
+                if self.bytes.get(self.pos) == Some(&b'}') {
+                    self.pos += 1;
+                    return Ok(Json::Object(fields));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.skip_whitespace();
+                    self.expect(":")?;
+                    fields.push((key, self.value()?));
+                    self.skip_whitespace();
+                    match self.next()? {
+                        b',' => continue,
+                        b'}' => return Ok(Json::Object(fields)),
+                        _ => return Err(format!("expected , or }} at byte {}", self.pos - 1)),
+                    }
+                }
+            }
+            _ => self.number(),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+        while self.bytes.get(self.pos).is_some_and(|b| b.is_ascii_digit() || b"+-.eE".contains(b)) {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap();
+        text.parse().map(Json::Number).map_err(|_| format!("invalid number {:?} at byte {}", text, start))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect("\"")?;
+        let mut out = Vec::new();
+        loop {
+            match self.next()? {
+                b'"' => return String::from_utf8(out).map_err(|e| e.to_string()),
+                b'\\' => {
+                    let unescaped = match self.next()? {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        other => return Err(format!("invalid escape \\{} at byte {}", other as char, self.pos - 1)),
+                    };
+                    out.extend_from_slice(unescaped.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                byte => out.push(byte),
+            }
+        }
+    }
+
+    // The four hex digits after \u, plus a second escape for a surrogate pair
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            self.expect("\\u")?;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(format!("unpaired surrogate at byte {}", self.pos - 4));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| format!("invalid code point {:x} at byte {}", code, self.pos - 4))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self.bytes.get(self.pos..self.pos + 4).ok_or("unexpected end of input")?;
+        let code = std::str::from_utf8(digits).ok().and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or_else(|| format!("invalid \\u escape at byte {}", self.pos))?;
+        self.pos += 4;
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::{ComparisonReport, ComparisonRow, Verdict};
+
+    #[test]
+    fn strings_round_trip_through_escaping() {
+        let tricky = "quote \" backslash \\ newline \n tab \t bell \u{7} accent é emoji 🦀";
+        let escaped = string(tricky);
+        assert!(escaped.contains("\\u0007"), "{}", escaped);
+        assert_eq!(parse(&escaped).unwrap(), Json::String(tricky.to_string()));
+    }
+
+    #[test]
+    fn surrogate_pairs_are_decoded() {
+        assert_eq!(parse("\"\\ud83e\\udd80\"").unwrap(), Json::String("🦀".to_string()));
+    }
+
+    #[test]
+    fn comparison_report_round_trips() {
+        let report = ComparisonReport {
+            engines: vec!["RocksDB (LSM)".to_string(), "Sled \"B-Tree\"\n".to_string()],
+            rows: vec![
+                ComparisonRow {
+                    metric: "Throughput".to_string(),
+                    cells: vec!["1200.0 ops/s".to_string(), "800.5 ops/s".to_string()],
+                    values: vec![1200.0, 800.5],
+                    verdict: Verdict::Winner { engine: "RocksDB (LSM)".to_string(), margin: 1.5 },
+                },
+                ComparisonRow {
+                    metric: "P99 Read".to_string(),
+                    cells: vec!["0.1ms".to_string(), "0.1ms".to_string()],
+                    values: vec![0.1, 0.1],
+                    verdict: Verdict::Tie,
+                },
+                ComparisonRow {
+                    metric: "Version".to_string(),
+                    cells: vec!["8.10.0".to_string(), "0.34.7".to_string()],
+                    values: Vec::new(),
+                    verdict: Verdict::NotRanked,
+                },
+            ],
+        };
+        assert_eq!(ComparisonReport::from_json(&report.to_json()).unwrap(), report);
+    }
+}
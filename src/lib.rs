@@ -0,0 +1,12 @@
+pub mod benchmark;
+mod corpus;
+mod json;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod trace;
+
+pub use benchmark::{
+    compare_engines, create_engine, list_engines, run_workload, Benchmark, BenchmarkResult,
+    Capabilities, ComparisonReport, EngineConfig, EngineMetrics, StorageEngine, ENGINES,
+};
+pub use corpus::Corpus;
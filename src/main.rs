@@ -1,5 +1,12 @@
-mod benchmark;
-
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    benchmark::compare_engines()
-}
\ No newline at end of file
+    match std::env::args().nth(1).as_deref() {
+        Some("engines") => db_bench::list_engines(),
+        // Prints a table saved with compare_output again
+        Some("report") => {
+            let path = std::env::args().nth(2).ok_or("usage: db-bench report <compare_output.json>")?;
+            db_bench::ComparisonReport::from_json(&std::fs::read_to_string(path)?)?.print();
+            Ok(())
+        }
+        _ => db_bench::compare_engines(&db_bench::Benchmark::new()),
+    }
+}
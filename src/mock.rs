@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::benchmark::{EngineMetrics, KvPairs, StorageEngine};
+
+// In-memory engine with fixed artificial latencies and injected failures,
+// for exercising the harness without disk I/O. Deterministic apart from
+// the sleeps themselves.
+pub struct MockEngine {
+    pub latency: MockLatency,
+    // Every n-th call (counting all operations) returns an error instead of running
+    pub fail_every: Option<u64>,
+    data: Mutex<BTreeMap<Vec<u8>, Vec<u8>>>,
+    calls: AtomicU64,
+    counts: MockCounts,
+    bytes_written: AtomicU64,
+}
+
+// Added to each call of the matching kind; zero skips the sleep
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MockLatency {
+    pub put: Duration,
+    pub get: Duration,
+    pub delete: Duration,
+    pub scan: Duration, // range and prefix scans
+    pub flush: Duration,
+}
+
+#[derive(Debug, Default)]
+struct MockCounts {
+    puts: AtomicU64,
+    gets: AtomicU64,
+    deletes: AtomicU64,
+    scans: AtomicU64,
+    flushes: AtomicU64,
+}
+
+// Calls that reached the engine, including failed ones
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MockOpCounts {
+    pub puts: u64,
+    pub gets: u64,
+    pub deletes: u64,
+    pub scans: u64,
+    pub flushes: u64,
+}
+
+impl MockEngine {
+    pub fn new() -> Self {
+        Self {
+            latency: MockLatency::default(),
+            fail_every: None,
+            data: Mutex::new(BTreeMap::new()),
+            calls: AtomicU64::new(0),
+            counts: MockCounts::default(),
+            bytes_written: AtomicU64::new(0),
+        }
+    }
+
+    pub fn op_counts(&self) -> MockOpCounts {
+        MockOpCounts {
+            puts: self.counts.puts.load(Ordering::Relaxed),
+            gets: self.counts.gets.load(Ordering::Relaxed),
+            deletes: self.counts.deletes.load(Ordering::Relaxed),
+            scans: self.counts.scans.load(Ordering::Relaxed),
+            flushes: self.counts.flushes.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Counts the call, sleeps for its latency, and fails it if it's the n-th
+    fn enter(&self, counter: &AtomicU64, latency: Duration, op: &str) -> Result<(), Box<dyn std::error::Error>> {
+        counter.fetch_add(1, Ordering::Relaxed);
+        let call = self.calls.fetch_add(1, Ordering::Relaxed) + 1;
+        if !latency.is_zero() {
+            thread::sleep(latency);
+        }
+        match self.fail_every {
+            Some(n) if n > 0 && call.is_multiple_of(n) => Err(format!("mock {} failure (call {})", op, call).into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for MockEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StorageEngine for MockEngine {
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.enter(&self.counts.puts, self.latency.put, "put")?;
+        self.bytes_written.fetch_add((key.len() + value.len()) as u64, Ordering::Relaxed);
+        self.data.lock().unwrap().insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        self.enter(&self.counts.gets, self.latency.get, "get")?;
+        Ok(self.data.lock().unwrap().get(key).cloned())
+    }
+
+    fn delete(&self, key: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.enter(&self.counts.deletes, self.latency.delete, "delete")?;
+        self.data.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    fn range_scan(&self, start: &[u8], limit: usize) -> Result<KvPairs, Box<dyn std::error::Error>> {
+        self.enter(&self.counts.scans, self.latency.scan, "scan")?;
+        Ok(self.data.lock().unwrap().range(start.to_vec()..)
+            .take(limit)
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect())
+    }
+
+    fn prefix_scan(&self, prefix: &[u8]) -> Result<KvPairs, Box<dyn std::error::Error>> {
+        self.enter(&self.counts.scans, self.latency.scan, "prefix scan")?;
+        Ok(self.data.lock().unwrap().range(prefix.to_vec()..)
+            .take_while(|(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect())
+    }
+
+    fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.enter(&self.counts.flushes, self.latency.flush, "flush")
+    }
+
+    fn engine_name(&self) -> &str {
+        "Mock"
+    }
+
+    fn logical_bytes_written(&self) -> Option<u64> {
+        Some(self.bytes_written.load(Ordering::Relaxed))
+    }
+
+    // Nothing is amplified in memory; usage is the live keys and values
+    fn metrics(&self) -> EngineMetrics {
+        let live_bytes: usize = self.data.lock().unwrap().iter().map(|(k, v)| k.len() + v.len()).sum();
+        EngineMetrics {
+            write_amplification: 1.0,
+            space_amplification: 1.0,
+            compression_ratio: 1.0,
+            cache_hit_rate: None,
+            bloom_false_positive_rate: None,
+            memory_usage_mb: live_bytes as f64 / 1024.0 / 1024.0,
+            compaction_stats: (0, 0),
+            levels: Vec::new(),
+            compaction_pending: None,
+            column_families: Vec::new(),
+            physical_bytes_written: Some(self.bytes_written.load(Ordering::Relaxed)),
+        }
+    }
+}
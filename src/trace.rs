@@ -0,0 +1,67 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+// One traced operation: kind tag, key, and value length. Encoded as
+// kind (u8), key length (u16 LE), key bytes, value length (u32 LE).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TraceRecord {
+    pub kind: u8,
+    pub key: Vec<u8>,
+    pub value_len: u32,
+}
+
+pub(crate) struct TraceWriter {
+    out: BufWriter<File>,
+}
+
+impl TraceWriter {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self { out: BufWriter::new(File::create(path)?) })
+    }
+
+    pub fn write(&mut self, record: &TraceRecord) -> io::Result<()> {
+        let key_len = u16::try_from(record.key.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "trace key longer than 65535 bytes"))?;
+        self.out.write_all(&[record.kind])?;
+        self.out.write_all(&key_len.to_le_bytes())?;
+        self.out.write_all(&record.key)?;
+        self.out.write_all(&record.value_len.to_le_bytes())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+pub(crate) struct TraceReader {
+    input: BufReader<File>,
+}
+
+impl TraceReader {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Ok(Self { input: BufReader::new(File::open(path)?) })
+    }
+
+    fn read_record(&mut self) -> io::Result<Option<TraceRecord>> {
+        let mut kind = [0u8; 1];
+        if self.input.read(&mut kind)? == 0 {
+            return Ok(None);
+        }
+        let mut key_len = [0u8; 2];
+        self.input.read_exact(&mut key_len)?;
+        let mut key = vec![0u8; u16::from_le_bytes(key_len) as usize];
+        self.input.read_exact(&mut key)?;
+        let mut value_len = [0u8; 4];
+        self.input.read_exact(&mut value_len)?;
+        Ok(Some(TraceRecord { kind: kind[0], key, value_len: u32::from_le_bytes(value_len) }))
+    }
+}
+
+impl Iterator for TraceReader {
+    type Item = io::Result<TraceRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}